@@ -31,7 +31,6 @@ PROFILE_DIR="$NAJ_CONFIG_PATH/profiles"
 # SSH 和 仓库
 SSH_DIR="$BASE_DIR/ssh_keys"
 REPO_DIR="$BASE_DIR/repo"
-ALLOWED_SIGNERS="$BASE_DIR/allowed_signers"
 
 # 颜色
 PASS='\033[0;32m'
@@ -64,19 +63,17 @@ log "Sandbox initialized at: $BASE_DIR"
 log "Naj Config: $NAJ_TOML"
 
 # ==========================================
-# 1. 准备 SSH 密钥与信任链
+# 1. 准备 SSH 密钥
 # ==========================================
-log "Generating Keys & Establishing Trust..."
+log "Generating Keys..."
 
 # Alice
 ssh-keygen -t ed25519 -C "alice@corp.com" -f "$SSH_DIR/id_alice" -N "" -q
-echo "alice@corp.com $(cat $SSH_DIR/id_alice.pub)" >> "$ALLOWED_SIGNERS"
 
 # Bob
 ssh-keygen -t ed25519 -C "bob@home.org" -f "$SSH_DIR/id_bob" -N "" -q
-echo "bob@home.org $(cat $SSH_DIR/id_bob.pub)" >> "$ALLOWED_SIGNERS"
 
-ok "Keys generated and added to allowed_signers"
+ok "Keys generated"
 
 # ==========================================
 # 2. 创建 Profiles (带 SSH 签名配置)
@@ -87,29 +84,20 @@ log "Creating Naj Profiles..."
 $NAJ_BIN -c "Alice Corp" "alice@corp.com" alice
 # 追加详细配置
 cat >> "$PROFILE_DIR/alice.gitconfig" <<EOF
-[gpg]
-    format = ssh
-[user]
-    signingkey = $SSH_DIR/id_alice.pub
-[commit]
-    gpgsign = true
 [core]
     sshCommand = ssh -i $SSH_DIR/id_alice -F /dev/null -o IdentitiesOnly=yes -o StrictHostKeyChecking=no
 EOF
+# 签名配置与 allowed_signers 由 naj 维护
+$NAJ_BIN key sign alice
 ok "Profile 'alice' created"
 
 # --> Bob Profile
 $NAJ_BIN -c "Bob Home" "bob@home.org" bob
 cat >> "$PROFILE_DIR/bob.gitconfig" <<EOF
-[gpg]
-    format = ssh
-[user]
-    signingkey = $SSH_DIR/id_bob.pub
-[commit]
-    gpgsign = true
 [core]
     sshCommand = ssh -i $SSH_DIR/id_bob -F /dev/null -o IdentitiesOnly=yes -o StrictHostKeyChecking=no
 EOF
+$NAJ_BIN key sign bob
 ok "Profile 'bob' created"
 
 # ==========================================
@@ -117,8 +105,6 @@ ok "Profile 'bob' created"
 # ==========================================
 cd "$REPO_DIR"
 git init --quiet
-# 关键修复 2: 告诉 Git 信任这些公钥，否则签名状态会是 'U'
-git config gpg.ssh.allowedSignersFile "$ALLOWED_SIGNERS"
 
 # ==========================================
 # 4. 辅助函数
@@ -0,0 +1,270 @@
+use crate::config::NajConfig;
+use crate::git::{
+    dirty_config_findings, effective_value, get_profile_path, git_in, profile_value, run_switch,
+};
+use crate::guard::rule_profile;
+use crate::manage::profile_ids;
+use crate::profile::ProfileId;
+use crate::status::{bound_profiles, included_profile};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// A repository found while walking a directory tree.
+pub struct FoundRepo {
+    pub path: PathBuf,
+    pub bare: bool,
+}
+
+// A bare repository has no `.git`; its own directory holds HEAD, objects and refs.
+fn is_bare_repo(dir: &Path) -> bool {
+    dir.join("HEAD").is_file() && dir.join("objects").is_dir() && dir.join("refs").is_dir()
+}
+
+// Finds the repositories under `root`, in path order. A `.git` directory marks
+// a checkout, a `.git` file a linked worktree (or a checkout with a separate
+// git dir). The walk does not descend into repositories or follow symlinks.
+pub fn find_repos(root: &Path) -> Result<Vec<FoundRepo>> {
+    let root = root
+        .canonicalize()
+        .with_context(|| format!("Cannot read directory {:?}", root))?;
+    let mut repos = Vec::new();
+    let mut pending = vec![root];
+
+    while let Some(dir) = pending.pop() {
+        if dir.join(".git").exists() {
+            repos.push(FoundRepo {
+                path: dir,
+                bare: false,
+            });
+            continue;
+        }
+        if is_bare_repo(&dir) {
+            repos.push(FoundRepo {
+                path: dir,
+                bare: true,
+            });
+            continue;
+        }
+
+        // Unreadable directories are skipped rather than failing the walk
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                pending.push(entry.path());
+            }
+        }
+    }
+
+    repos.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(repos)
+}
+
+// What the audit found out about one repository.
+struct RepoReport {
+    profile: Option<(ProfileId, &'static str)>,
+    email: Option<String>,
+    signing: String,
+    findings: Vec<String>,
+    expected: Option<ProfileId>,
+}
+
+// The profile a repository uses: a locally included profile, one the global
+// config includes for it (directory rules), or else the profile whose email
+// the local config carries (the Override strategies).
+fn used_profile(config: &NajConfig, repo: &Path) -> Result<Option<(ProfileId, &'static str)>> {
+    let (bound, _) = bound_profiles(config, repo)?;
+    if let Some(id) = bound.last() {
+        return Ok(Some((id.clone(), "include")));
+    }
+    if let Some(found) = included_profile(config, repo)? {
+        return Ok(Some(found));
+    }
+
+    let Some(email) = local_value(repo, "user.email")? else {
+        return Ok(None);
+    };
+    let mut owners = Vec::new();
+    for id in profile_ids(config)? {
+        let path = get_profile_path(config, &id)?;
+        if profile_value(&path, "user.email").ok().flatten().as_ref() == Some(&email) {
+            owners.push(id);
+        }
+    }
+    Ok(match owners.as_slice() {
+        [id] => Some((id.clone(), "override")),
+        _ => None,
+    })
+}
+
+// The value of `key` set in the repository's own config, including the
+// worktree's config.worktree when profiles are bound per worktree.
+fn local_value(repo: &Path, key: &str) -> Result<Option<String>> {
+    let output = git_in(repo)
+        .args(["config", "--show-scope", "--get", key])
+        .output()
+        .with_context(|| format!("Failed to read local value of {}", key))?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .trim_end_matches('\n')
+        .split_once('\t')
+        .filter(|(scope, _)| matches!(*scope, "local" | "worktree"))
+        .map(|(_, value)| value.to_string()))
+}
+
+// Whether commits are signed, and with which format. The flag is set when
+// signing is on but no key is configured, so every commit would fail.
+fn signing_status(repo: &Path) -> Result<(String, bool)> {
+    let enabled = effective_value(repo, "commit.gpgsign")?
+        .is_some_and(|v| matches!(v.to_ascii_lowercase().as_str(), "true" | "yes" | "on" | "1"));
+    if !enabled {
+        return Ok(("off".to_string(), false));
+    }
+    let format = effective_value(repo, "gpg.format")?.unwrap_or_else(|| "openpgp".to_string());
+    match effective_value(repo, "user.signingkey")?.filter(|k| !k.is_empty()) {
+        Some(_) => Ok((format!("on ({})", format), false)),
+        None => Ok((format!("on ({}), but no user.signingkey", format), true)),
+    }
+}
+
+// Inspects the repository at `repo`.
+fn inspect(config: &NajConfig, repo: &Path) -> Result<RepoReport> {
+    let profile = used_profile(config, repo)?;
+    let email = effective_value(repo, "user.email")?.filter(|e| !e.is_empty());
+    let (signing, missing_key) = signing_status(repo)?;
+
+    let override_profile = match &profile {
+        Some((id, "override")) => Some(get_profile_path(config, id)?),
+        _ => None,
+    };
+    let mut findings: Vec<String> =
+        dirty_config_findings(config, repo, override_profile.as_deref())?
+            .into_iter()
+            .map(|key| format!("dirty local config: {}", key))
+            .collect();
+
+    if let Some((id, _)) = &profile {
+        let profile_email = profile_value(&get_profile_path(config, id)?, "user.email")?;
+        if profile_email.is_some() && profile_email != email {
+            findings.push(format!(
+                "effective email '{}' is not the email of profile '{}' ({})",
+                email.as_deref().unwrap_or(""),
+                id,
+                profile_email.as_deref().unwrap_or("")
+            ));
+        }
+    }
+
+    let expected = rule_profile(config, repo)?;
+    if let Some((expected_id, reason)) = &expected {
+        match &profile {
+            Some((id, _)) if id == expected_id => {}
+            Some((id, _)) => findings.push(format!(
+                "bound to '{}' but {} selects '{}'",
+                id, reason, expected_id
+            )),
+            None => findings.push(format!(
+                "not bound, but {} selects '{}'",
+                reason, expected_id
+            )),
+        }
+    }
+    if missing_key {
+        findings.push("signing is on without a signing key".to_string());
+    }
+
+    Ok(RepoReport {
+        profile,
+        email,
+        signing,
+        findings,
+        expected: expected.map(|(id, _)| id),
+    })
+}
+
+// Walks `root` and reports the identity of every repository found. With `fix`,
+// repositories that disagree with their rules are switched to the
+// rule-selected profile.
+pub fn run_audit(config: &NajConfig, root: &Path, fix: bool, force: bool) -> Result<()> {
+    let repos = find_repos(root)?;
+    if repos.is_empty() {
+        println!("No git repositories found under {:?}", root);
+        return Ok(());
+    }
+
+    let mut with_findings = 0;
+    let mut fixed = 0;
+    for repo in &repos {
+        println!(
+            "{}{}",
+            repo.path.display(),
+            if repo.bare { " (bare)" } else { "" }
+        );
+        let report = match inspect(config, &repo.path) {
+            Ok(report) => report,
+            Err(e) => {
+                println!("  error:    {:#}", e);
+                with_findings += 1;
+                continue;
+            }
+        };
+
+        let profile = match &report.profile {
+            Some((id, how)) => format!("{} ({})", id, how),
+            None => "(none)".to_string(),
+        };
+        println!("  profile:  {}", profile);
+        println!(
+            "  email:    {}",
+            report.email.as_deref().unwrap_or("(unset)")
+        );
+        println!("  signing:  {}", report.signing);
+        for finding in &report.findings {
+            println!("  ⚠️  {}", finding);
+        }
+        if report.findings.is_empty() {
+            continue;
+        }
+        with_findings += 1;
+
+        if !fix {
+            continue;
+        }
+        let Some(expected) = &report.expected else {
+            println!("  no rule selects a profile, nothing to fix");
+            continue;
+        };
+        if repo.bare {
+            println!("  skipped fix: bare repository");
+            continue;
+        }
+        match run_switch(
+            config,
+            &repo.path,
+            expected,
+            config.strategies.switch,
+            force,
+            None,
+        ) {
+            Ok(()) => fixed += 1,
+            Err(e) => println!("  fix failed: {:#}", e),
+        }
+    }
+
+    println!();
+    print!(
+        "Audited {} repositories, {} with findings",
+        repos.len(),
+        with_findings
+    );
+    if fix {
+        print!(", {} fixed", fixed);
+    }
+    println!();
+    Ok(())
+}
@@ -1,27 +1,90 @@
+use crate::profile::ProfileId;
 use crate::utils::expand_path;
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(default)]
 pub struct Strategies {
-    pub clone: String,
+    // Used when binding a repository created by `naj <id> clone` / `init`
+    pub clone: SwitchStrategy,
     pub switch: SwitchStrategy,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl Default for Strategies {
+    fn default() -> Self {
+        Strategies {
+            clone: SwitchStrategy::IncludeHard,
+            switch: SwitchStrategy::IncludeSoft,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum SwitchStrategy {
+    #[default]
     IncludeSoft,
     IncludeHard,
     OverrideSoft,
     OverrideHard,
 }
 
-impl Default for SwitchStrategy {
-    fn default() -> Self {
-        SwitchStrategy::IncludeSoft
+impl SwitchStrategy {
+    // The Hard variant of the same family, used when `--force` is given.
+    pub fn hardened(self) -> Self {
+        match self {
+            SwitchStrategy::IncludeSoft | SwitchStrategy::IncludeHard => {
+                SwitchStrategy::IncludeHard
+            }
+            SwitchStrategy::OverrideSoft | SwitchStrategy::OverrideHard => {
+                SwitchStrategy::OverrideHard
+            }
+        }
+    }
+}
+
+impl FromStr for SwitchStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        // Sanitize input to handle potential trailing whitespace from manual edits
+        let s = s.trim();
+        match s {
+            "INCLUDE" => return Ok(SwitchStrategy::IncludeHard),
+            "OVERRIDE" => return Ok(SwitchStrategy::OverrideHard),
+            "override" => return Ok(SwitchStrategy::OverrideSoft),
+            "include" => return Ok(SwitchStrategy::IncludeSoft),
+            _ => {}
+        }
+
+        // Spelled-out aliases are case-insensitive since they carry no case semantics
+        match s.to_ascii_lowercase().replace('_', "-").as_str() {
+            "include-soft" => Ok(SwitchStrategy::IncludeSoft),
+            "include-hard" => Ok(SwitchStrategy::IncludeHard),
+            "override-soft" => Ok(SwitchStrategy::OverrideSoft),
+            "override-hard" => Ok(SwitchStrategy::OverrideHard),
+            _ => Err(anyhow!(
+                "unknown strategy '{}', expected one of: include, INCLUDE, override, OVERRIDE \
+                 (or include-soft, include-hard, override-soft, override-hard)",
+                s
+            )),
+        }
+    }
+}
+
+// Shown the way it is written in config.toml.
+impl fmt::Display for SwitchStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SwitchStrategy::IncludeSoft => "include",
+            SwitchStrategy::IncludeHard => "INCLUDE",
+            SwitchStrategy::OverrideSoft => "override",
+            SwitchStrategy::OverrideHard => "OVERRIDE",
+        })
     }
 }
 
@@ -30,13 +93,7 @@ impl Serialize for SwitchStrategy {
     where
         S: Serializer,
     {
-        let s = match self {
-            SwitchStrategy::IncludeSoft => "include",
-            SwitchStrategy::IncludeHard => "INCLUDE",
-            SwitchStrategy::OverrideSoft => "override",
-            SwitchStrategy::OverrideHard => "OVERRIDE",
-        };
-        serializer.serialize_str(s)
+        serializer.collect_str(self)
     }
 }
 
@@ -46,22 +103,71 @@ impl<'de> Deserialize<'de> for SwitchStrategy {
         D: Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        // Sanitize input to handle potential trailing whitespace from manual edits
-        Ok(match s.trim() {
-            "INCLUDE" => SwitchStrategy::IncludeHard,
-            "OVERRIDE" => SwitchStrategy::OverrideHard,
-            "override" => SwitchStrategy::OverrideSoft,
-            "include" => SwitchStrategy::IncludeSoft,
-            _ => SwitchStrategy::IncludeSoft, // Default fallback
-        })
+        // Unknown values are rejected rather than silently falling back, so a
+        // typo can never downgrade a Hard strategy to a Soft one.
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// Binds every repository whose git dir matches `path` to `profile`.
+// `path` uses git's `includeIf "gitdir:..."` pattern syntax verbatim.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DirRule {
+    pub path: String,
+    pub profile: ProfileId,
+}
+
+// Selects `profile` for `naj auto` when a remote URL matches `pattern`.
+// Patterns are written as `host:path` (e.g. `github.com:acme/*`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UrlRule {
+    pub pattern: String,
+    pub profile: ProfileId,
+}
+
+// An SSH host alias for `profile`: `naj hosts apply` writes a `Host <alias>`
+// block for it to the SSH config and makes the profile rewrite `host` URLs to
+// the alias, so every tool going through ssh picks the profile's key.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HostAlias {
+    pub profile: ProfileId,
+    pub host: String,
+    // Defaults to `<host>-<profile>`, e.g. `github.com-work`
+    #[serde(default)]
+    pub alias: Option<String>,
+    #[serde(default = "default_ssh_user")]
+    pub user: String,
+}
+
+impl HostAlias {
+    pub fn alias(&self) -> String {
+        self.alias
+            .clone()
+            .unwrap_or_else(|| format!("{}-{}", self.host, self.profile))
     }
 }
 
+fn default_ssh_user() -> String {
+    "git".to_string()
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct NajConfig {
     #[serde(default)]
     pub strategies: Strategies,
     pub profile_dir: String,
+    // Where `naj key gen` stores generated SSH keys
+    #[serde(default = "default_key_dir")]
+    pub key_dir: String,
+    // The SSH client config `naj hosts apply` maintains its block in
+    #[serde(default = "default_ssh_config")]
+    pub ssh_config: String,
+    #[serde(default)]
+    pub rules: Vec<DirRule>,
+    #[serde(default)]
+    pub url_rules: Vec<UrlRule>,
+    #[serde(default)]
+    pub host_aliases: Vec<HostAlias>,
 }
 
 impl Default for NajConfig {
@@ -69,10 +175,23 @@ impl Default for NajConfig {
         NajConfig {
             strategies: Strategies::default(),
             profile_dir: "~/.config/naj/profiles".to_string(),
+            key_dir: default_key_dir(),
+            ssh_config: default_ssh_config(),
+            rules: Vec::new(),
+            url_rules: Vec::new(),
+            host_aliases: Vec::new(),
         }
     }
 }
 
+fn default_key_dir() -> String {
+    "~/.ssh".to_string()
+}
+
+fn default_ssh_config() -> String {
+    "~/.ssh/config".to_string()
+}
+
 pub fn get_config_root() -> Result<PathBuf> {
     if let Ok(path) = std::env::var("NAJ_CONFIG_PATH") {
         return Ok(PathBuf::from(path));
@@ -91,7 +210,8 @@ pub fn load_config() -> Result<NajConfig> {
     }
 
     let content = fs::read_to_string(&config_path).context("Failed to read config file")?;
-    let config: NajConfig = toml::from_str(&content).context("Failed to parse config file")?;
+    let config: NajConfig = toml::from_str(&content)
+        .with_context(|| format!("Failed to parse config file {:?}", config_path))?;
     Ok(config)
 }
 
@@ -101,30 +221,67 @@ fn initialize_config(root: &Path, config_path: &Path) -> Result<NajConfig> {
 
     // Determine default profile_dir based on environment to support testing isolation.
     // When NAJ_CONFIG_PATH is set, we prefer keeping profiles relative to it.
-    let profile_dir_str = if let Ok(env_path) = std::env::var("NAJ_CONFIG_PATH") {
-        let p = PathBuf::from(env_path).join("profiles");
-        p.to_string_lossy().to_string()
-    } else {
-        "~/.config/naj/profiles".to_string()
-    };
+    let (profile_dir_str, key_dir_str, ssh_config_str) =
+        if let Ok(env_path) = std::env::var("NAJ_CONFIG_PATH") {
+            let root = PathBuf::from(env_path);
+            (
+                root.join("profiles").to_string_lossy().to_string(),
+                root.join("keys").to_string_lossy().to_string(),
+                root.join("ssh_config").to_string_lossy().to_string(),
+            )
+        } else {
+            (
+                "~/.config/naj/profiles".to_string(),
+                default_key_dir(),
+                default_ssh_config(),
+            )
+        };
 
     // Manual formatting allows us to include helpful comments in the generated file.
     // On Windows, we must escape backslashes to ensure the TOML string literal is valid.
     let escaped_profile_dir = profile_dir_str.replace("\\", "\\\\");
+    let escaped_key_dir = key_dir_str.replace("\\", "\\\\");
+    let escaped_ssh_config = ssh_config_str.replace("\\", "\\\\");
 
     let generated_toml = format!(
         r#"# Naj Configuration
 
 profile_dir = "{}"
 
+# Where `naj key gen` stores generated SSH keys
+key_dir = "{}"
+
+# SSH client config in which `naj hosts apply` maintains its Host aliases
+ssh_config = "{}"
+
 [strategies]
 # include: Include the profile file in the git config
 # override: Override the git config with the profile file
 # INCLUDE, OVERRIDE: clear the value in the git config and apply config
+# Aliases: include-soft, include-hard, override-soft, override-hard
 clone = "INCLUDE" # Hard strategy
 switch = "include" # Soft strategy
+
+# Directory rules: bind every repository under a path to a profile.
+# Run `naj rules apply` after editing to regenerate the git includes.
+# When several rules match, the last one wins.
+# [[rules]]
+# path = "~/work/**"
+# profile = "work"
+
+# Remote URL rules: let `naj auto` pick a profile from the repository's remotes.
+# When several rules match, the last one wins.
+# [[url_rules]]
+# pattern = "github.com:acme/*"
+# profile = "work"
+
+# SSH host aliases: give a profile its own `Host` entry for a forge.
+# Run `naj hosts apply` after editing to regenerate them.
+# [[host_aliases]]
+# profile = "work"
+# host = "github.com"   # alias defaults to "github.com-work"
 "#,
-        escaped_profile_dir
+        escaped_profile_dir, escaped_key_dir, escaped_ssh_config
     );
 
     fs::write(config_path, &generated_toml).context("Failed to write default config")?;
@@ -1,10 +1,22 @@
+use crate::audit;
 use crate::config::{NajConfig, SwitchStrategy};
+use crate::gitconfig::{self, GitConfig};
+use crate::gpg;
+use crate::guard;
+use crate::history;
+use crate::hosts;
+use crate::manage;
 use crate::naj_debug;
+use crate::plan::{PlanFormat, SwitchPlan};
+use crate::profile::{is_git_command, join_ids, ProfileId};
+use crate::rules;
 use crate::sanitizer;
-use crate::utils::expand_path;
-use anyhow::{anyhow, Context, Result};
+use crate::snapshot::ConfigSnapshot;
+use crate::utils::{expand_path, shell_quote};
+use anyhow::{anyhow, bail, Context, Result};
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::OnceLock;
 
 enum Action {
     Setup,
@@ -12,7 +24,18 @@ enum Action {
     Switch,
 }
 
-pub fn run(config: &NajConfig, profile_id: &str, args: &[String], force: bool) -> Result<()> {
+// `repo` is the directory naj operates in (`-C`), like git's own working directory.
+// `strategy` overrides the configured switch/clone strategy for this invocation.
+// With `dry_run`, a switch only prints its plan in the given format.
+pub fn run(
+    config: &NajConfig,
+    repo: &Path,
+    profile_id: &ProfileId,
+    args: &[String],
+    force: bool,
+    strategy: Option<SwitchStrategy>,
+    dry_run: Option<PlanFormat>,
+) -> Result<()> {
     let action = if args.is_empty() {
         Action::Switch
     } else if args[0] == "clone" || args[0] == "init" {
@@ -21,29 +44,317 @@ pub fn run(config: &NajConfig, profile_id: &str, args: &[String], force: bool) -
         Action::Exec
     };
 
+    // Refuse profiles whose inheritance chain is broken before touching anything
+    explain_git_command(config, repo, profile_id, args)?;
+    get_profile_path(config, profile_id)?;
+    manage::profile_ancestors(config, profile_id)?;
+
+    if dry_run.is_some() && !matches!(action, Action::Switch) {
+        bail!("--dry-run is only supported when switching a repository's profile");
+    }
+
     match action {
-        Action::Exec => run_exec(config, profile_id, args),
-        Action::Switch => run_switch(config, profile_id, force),
-        Action::Setup => run_setup(config, profile_id, args),
+        Action::Exec => run_exec(config, repo, profile_id, args),
+        Action::Switch => run_switch(
+            config,
+            repo,
+            profile_id,
+            strategy.unwrap_or(config.strategies.switch),
+            force,
+            dry_run,
+        ),
+        Action::Setup => run_setup(
+            config,
+            repo,
+            profile_id,
+            args,
+            strategy.unwrap_or(config.strategies.clone),
+            force,
+        ),
+    }
+}
+
+// Switches every repository under `root` to a profile. A repository that fails
+// does not stop the others; the results are summarised at the end.
+pub fn run_recursive(
+    config: &NajConfig,
+    profile_id: &ProfileId,
+    root: &Path,
+    force: bool,
+    strategy: Option<SwitchStrategy>,
+    dry_run: Option<PlanFormat>,
+) -> Result<()> {
+    get_profile_path(config, profile_id)?;
+    manage::profile_ancestors(config, profile_id)?;
+
+    let repos = audit::find_repos(root)?;
+    if repos.is_empty() {
+        bail!("No git repositories found under {:?}", root);
     }
+    let strategy = strategy.unwrap_or(config.strategies.switch);
+    switch_each(
+        config,
+        &repos,
+        profile_id,
+        strategy,
+        force,
+        dry_run,
+        "repositories",
+    )
+}
+
+// Switches every initialised submodule of `repo`, recursively, to a profile.
+// The superproject itself is switched by the caller.
+pub fn run_submodules(
+    config: &NajConfig,
+    repo: &Path,
+    profile_id: &ProfileId,
+    force: bool,
+    strategy: Option<SwitchStrategy>,
+    dry_run: Option<PlanFormat>,
+) -> Result<()> {
+    // `foreach` only visits submodules that are initialised and checked out
+    let output = git_in(repo)
+        .args(["submodule", "foreach", "--quiet", "--recursive", "pwd"])
+        .output()
+        .context("Failed to list submodules")?;
+    if !output.status.success() {
+        bail!(
+            "Failed to list submodules: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let submodules: Vec<audit::FoundRepo> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|path| audit::FoundRepo {
+            path: PathBuf::from(path),
+            bare: false,
+        })
+        .collect();
+    if submodules.is_empty() {
+        println!("No initialised submodules");
+        return Ok(());
+    }
+
+    println!();
+    let strategy = strategy.unwrap_or(config.strategies.switch);
+    switch_each(
+        config,
+        &submodules,
+        profile_id,
+        strategy,
+        force,
+        dry_run,
+        "submodules",
+    )
 }
 
-// Helper to construct the full path to a profile's .gitconfig file.
-fn get_profile_path(config: &NajConfig, id: &str) -> Result<PathBuf> {
-    let profile_dir = expand_path(&config.profile_dir)?;
-    let p = profile_dir.join(format!("{}.gitconfig", id));
+// Switches each of `repos` on its own and summarises the results; `noun`
+// names them in the summary.
+fn switch_each(
+    config: &NajConfig,
+    repos: &[audit::FoundRepo],
+    profile_id: &ProfileId,
+    strategy: SwitchStrategy,
+    force: bool,
+    dry_run: Option<PlanFormat>,
+    noun: &str,
+) -> Result<()> {
+    let mut switched = 0;
+    let mut skipped = Vec::new();
+    let mut failed = Vec::new();
+    for repo in repos {
+        println!("==> {}", repo.path.display());
+        if repo.bare {
+            println!("Skipped: bare repository");
+            skipped.push(&repo.path);
+            continue;
+        }
+        match run_switch(config, &repo.path, profile_id, strategy, force, dry_run) {
+            Ok(()) => switched += 1,
+            Err(err) => {
+                println!("Error: {:#}", err);
+                failed.push((&repo.path, err));
+            }
+        }
+    }
+
+    if dry_run.is_some() {
+        return Ok(());
+    }
+    println!(
+        "\nSwitched {} of {} {} to profile '{}'",
+        switched,
+        repos.len(),
+        noun,
+        profile_id
+    );
+    for path in &skipped {
+        println!("  skipped  {} (bare repository)", path.display());
+    }
+    for (path, err) in &failed {
+        println!("  failed   {}: {:#}", path.display(), err);
+    }
+    if !failed.is_empty() {
+        bail!(
+            "Could not switch {} of {} {}",
+            failed.len(),
+            repos.len(),
+            noun
+        );
+    }
+    Ok(())
+}
+
+// Selects a profile from the URL rules and then behaves exactly like `naj <id> ...`.
+// For `clone` the URL being cloned decides; otherwise the current repo's remotes do.
+pub fn run_auto(
+    config: &NajConfig,
+    repo: &Path,
+    args: &[String],
+    force: bool,
+    strategy: Option<SwitchStrategy>,
+    dry_run: Option<PlanFormat>,
+) -> Result<()> {
+    let (source, profile_id) = if args.first().map(String::as_str) == Some("clone") {
+        let url = parse_clone_args(args).url;
+        let url = url.ok_or_else(|| anyhow!("No repository URL given to clone"))?;
+        let rule = rules::match_url_rule(config, url)
+            .ok_or_else(|| anyhow!("No URL rule matches '{}'", url))?;
+        (url.to_string(), rule.profile.clone())
+    } else {
+        let remotes = list_remote_urls(repo)?;
+        remotes
+            .iter()
+            .find_map(|(name, url)| {
+                rules::match_url_rule(config, url)
+                    .map(|rule| (format!("{} ({})", name, url), rule.profile.clone()))
+            })
+            .ok_or_else(|| anyhow!("No URL rule matches any remote of this repository"))?
+    };
+
+    eprintln!("Auto-selected profile '{}' from {}", profile_id, source);
+    run(config, repo, &profile_id, args, force, strategy, dry_run)
+}
+
+// Lists `(name, url)` for every configured remote. `origin` is moved to the
+// front so it wins when several remotes match different rules.
+pub fn list_remote_urls(repo: &Path) -> Result<Vec<(String, String)>> {
+    let output = git_in(repo)
+        .args(["config", "--get-regexp", r"^remote\..*\.url$"])
+        .output()
+        .context("Failed to read remote URLs")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut remotes = Vec::new();
+    for line in stdout.lines() {
+        if let Some((key, url)) = line.split_once(' ') {
+            let name = key
+                .strip_prefix("remote.")
+                .and_then(|k| k.strip_suffix(".url"))
+                .unwrap_or(key);
+            remotes.push((name.to_string(), url.trim().to_string()));
+        }
+    }
+    remotes.sort_by_key(|(name, _)| name != "origin");
+    Ok(remotes)
+}
+
+// Path of a profile's .gitconfig file, whether or not it exists yet.
+pub fn profile_file(config: &NajConfig, id: &ProfileId) -> Result<PathBuf> {
+    Ok(id.file_in(&get_profile_dir(config)?))
+}
+
+// Helper to construct the full path to an existing profile's .gitconfig file.
+pub fn get_profile_path(config: &NajConfig, id: &ProfileId) -> Result<PathBuf> {
+    let p = profile_file(config, id)?;
     if !p.exists() {
-        return Err(anyhow!("Profile '{}' not found at {:?}", id, p));
+        let similar = manage::similar_profiles(config, id)?;
+        if similar.is_empty() {
+            return Err(anyhow!("Profile '{}' not found at {:?}", id, p));
+        }
+        return Err(anyhow!(
+            "Profile '{}' not found at {:?}. Did you mean '{}'?",
+            id,
+            p,
+            join_ids(&similar, "' or '")
+        ));
     }
     Ok(p)
 }
 
-fn is_mocking() -> bool {
+// A git command typed without a profile (`naj commit -m x`) parses as a
+// profile named "commit"; point at the intended form instead.
+fn explain_git_command(
+    config: &NajConfig,
+    repo: &Path,
+    command: &ProfileId,
+    args: &[String],
+) -> Result<()> {
+    if !is_git_command(command.as_str()) || profile_file(config, command)?.exists() {
+        return Ok(());
+    }
+
+    let ids = manage::profile_ids(config)?;
+    let expected = if ensure_git_repo(repo).is_ok() {
+        guard::expected_profile(config, repo)
+            .ok()
+            .flatten()
+            .map(|(id, _)| id)
+    } else {
+        None
+    };
+    let profile = match (expected, ids.as_slice()) {
+        (Some(id), _) => id.to_string(),
+        (None, [only]) => only.to_string(),
+        _ => "<profile>".to_string(),
+    };
+
+    let mut words = vec![command.to_string()];
+    words.extend(args.iter().map(|a| shell_quote(a)));
+    let mut message = format!(
+        "'{}' is a git command, not a profile. Did you mean `naj {} {}`?",
+        command,
+        profile,
+        words.join(" ")
+    );
+    if profile == "<profile>" && !ids.is_empty() {
+        message.push_str(&format!("\nAvailable profiles: {}", join_ids(&ids, ", ")));
+    }
+    bail!(message)
+}
+
+pub fn is_mocking() -> bool {
     std::env::var("NAJ_MOCKING").is_ok()
 }
 
+// The `--git-dir` from the command line, keyed by the repository it was given
+// for. Only git commands for that repository receive it, so runs that visit
+// other repositories (--recursive, --submodules, audit) still use their own.
+static GIT_DIR_OVERRIDE: OnceLock<(PathBuf, PathBuf)> = OnceLock::new();
+
+pub fn set_git_dir_override(repo: &Path, git_dir: PathBuf) {
+    let _ = GIT_DIR_OVERRIDE.set((repo_key(repo), git_dir));
+}
+
+fn repo_key(repo: &Path) -> PathBuf {
+    repo.canonicalize().unwrap_or_else(|_| repo.to_path_buf())
+}
+
+// A `git` command that operates on the repository at `repo`.
+pub fn git_in(repo: &Path) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(repo);
+    if let Some((key, git_dir)) = GIT_DIR_OVERRIDE.get() {
+        if *key == repo_key(repo) {
+            cmd.arg("--git-dir").arg(git_dir);
+        }
+    }
+    cmd
+}
+
 // Execution helper that handles dry-runs during testing.
-fn run_command(cmd: &mut Command) -> Result<()> {
+pub fn run_command(cmd: &mut Command) -> Result<()> {
     if is_mocking() {
         eprintln!("[DRY-RUN] {:?}", cmd);
         return Ok(());
@@ -55,113 +366,369 @@ fn run_command(cmd: &mut Command) -> Result<()> {
     Ok(())
 }
 
-fn get_profile_dir(config: &NajConfig) -> Result<PathBuf> {
+pub fn get_profile_dir(config: &NajConfig) -> Result<PathBuf> {
     expand_path(&config.profile_dir)
 }
 
-// Locates and removes existing Naj profile inclusions from the local git config
-// to prevent configuration pollution or conflicts.
-fn clean_existing_profiles(profile_dir: &Path) -> Result<()> {
-    let output = Command::new("git")
-        .args(&["config", "--local", "--get-all", "include.path"])
-        .output()?;
+// Recognises `include.path` values that point at a Naj profile file.
+pub fn is_profile_include(val: &str, profile_dir: &Path) -> bool {
+    let path_obj = Path::new(val);
+    let match_path = val.contains(&profile_dir.to_string_lossy().to_string())
+        || (val.contains("/profiles/") && val.ends_with(".gitconfig"));
+    let match_name = path_obj
+        .file_name()
+        .map(|n| n.to_string_lossy().ends_with(".gitconfig"))
+        .unwrap_or(false);
+    match_path || match_name
+}
+
+// Returns the local `include.path` values that refer to Naj profiles, in the
+// order git reads them: a per-worktree binding comes last and wins.
+pub fn local_profile_includes(repo: &Path, profile_dir: &Path) -> Result<Vec<String>> {
+    let mut includes = Vec::new();
+    for config_path in local_config_paths(repo)? {
+        if !config_path.exists() {
+            continue;
+        }
+        let local = GitConfig::load(&config_path)?;
+        includes.extend(
+            local
+                .get_all("include.path")
+                .into_iter()
+                .filter(|val| is_profile_include(val, profile_dir))
+                .map(String::from),
+        );
+    }
+    Ok(includes)
+}
 
+// Path of a file in the git dir, as `git rev-parse --git-path` resolves it:
+// shared files like `config` live in the common dir of linked worktrees.
+fn git_path(repo: &Path, name: &str) -> Result<PathBuf> {
+    let output = git_in(repo)
+        .args(["rev-parse", "--git-path", name])
+        .output()
+        .with_context(|| format!("Failed to locate {}", name))?;
     if !output.status.success() {
+        return Err(anyhow!("Not a git repository: {}", repo.display()));
+    }
+    // The path is relative to `repo` unless the git dir lives elsewhere
+    Ok(repo.join(String::from_utf8_lossy(&output.stdout).trim_end_matches('\n')))
+}
+
+// Path of the repository's own config file (`.git/config` in a plain checkout).
+// Linked worktrees and their main checkout share it.
+pub fn local_config_path(repo: &Path) -> Result<PathBuf> {
+    git_path(repo, "config")
+}
+
+// Whether the repository reads per-worktree config (`extensions.worktreeConfig`).
+pub fn worktree_config_enabled(repo: &Path) -> Result<bool> {
+    let output = git_in(repo)
+        .args([
+            "config",
+            "--local",
+            "--bool",
+            "--get",
+            "extensions.worktreeConfig",
+        ])
+        .output()
+        .context("Failed to read extensions.worktreeConfig")?;
+    Ok(output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "true")
+}
+
+// The config file a switch binds the profile in: the worktree's own
+// `config.worktree` once extensions.worktreeConfig is on, the shared local
+// config otherwise.
+pub fn switch_config_path(repo: &Path) -> Result<PathBuf> {
+    if worktree_config_enabled(repo)? {
+        git_path(repo, "config.worktree")
+    } else {
+        local_config_path(repo)
+    }
+}
+
+// Every repository-level config file git reads for `repo`, in reading order.
+fn local_config_paths(repo: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![local_config_path(repo)?];
+    if worktree_config_enabled(repo)? {
+        paths.push(git_path(repo, "config.worktree")?);
+    }
+    Ok(paths)
+}
+
+// The `git config` option that writes to the file `switch_config_path` names.
+fn switch_scope(repo: &Path) -> Result<&'static str> {
+    Ok(if worktree_config_enabled(repo)? {
+        "--worktree"
+    } else {
+        "--local"
+    })
+}
+
+// Turns on extensions.worktreeConfig, so that switches from now on bind
+// the profile to a single worktree instead of every worktree of the repository.
+//
+// Switches only clean the worktree's own config.worktree, so a binding left in
+// the shared config would keep applying underneath every later one. The
+// profile includes and identity keys found there are therefore moved into the
+// config.worktree of each worktree, which keeps its current identity.
+pub fn enable_worktree_config(config: &NajConfig, repo: &Path) -> Result<()> {
+    ensure_git_repo(repo)?;
+    if worktree_config_enabled(repo)? {
         return Ok(());
     }
+    let shared_path = local_config_path(repo)?;
+    let shared = if shared_path.exists() {
+        GitConfig::load(&shared_path)?
+    } else {
+        GitConfig::default()
+    };
+    let binding = shared_binding(config, &shared)?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    for line in stdout.lines() {
-        let val = line.trim();
-        let path_obj = Path::new(val);
-        let match_path = val.contains(&profile_dir.to_string_lossy().to_string())
-            || (val.contains("/profiles/") && val.ends_with(".gitconfig"));
-        let match_name = path_obj
-            .file_name()
-            .map(|n| n.to_string_lossy().ends_with(".gitconfig"))
-            .unwrap_or(false);
-
-        if match_path || match_name {
-            let mut cmd = Command::new("git");
-            cmd.args(&["config", "--local", "--unset", "include.path", val]);
-            if is_mocking() {
-                eprintln!("[DRY-RUN] {:?}", cmd);
-            } else {
-                let _ = cmd.output();
+    let mut cmd = git_in(repo);
+    cmd.args(["config", "--local", "extensions.worktreeConfig", "true"]);
+    run_command(&mut cmd)?;
+
+    if !binding.entries.is_empty() {
+        for worktree in list_worktrees(repo)? {
+            for entry in &binding.entries {
+                let key = entry.key();
+                let mut cmd = git_in(&worktree);
+                cmd.args(["config", "--worktree", "--add", &key, entry.value_or_true()]);
+                run_command(&mut cmd)?;
             }
         }
+        for val in &binding.includes {
+            let mut cmd = git_in(repo);
+            cmd.args(["config", "--local", "--unset", "include.path", val]);
+            run_cleanup(&mut cmd, &format!("include.path '{}'", val))?;
+        }
+        for section in &binding.sections {
+            let mut cmd = git_in(repo);
+            cmd.args(["config", "--local", "--remove-section", section]);
+            run_cleanup(&mut cmd, &format!("section '{}'", section))?;
+        }
+        for key in &binding.keys {
+            let mut cmd = git_in(repo);
+            cmd.args(["config", "--local", "--unset-all", key]);
+            run_cleanup(&mut cmd, &format!("key '{}'", key))?;
+        }
     }
+    println!("Enabled extensions.worktreeConfig: profiles are now bound per worktree");
     Ok(())
 }
 
-fn apply_profile_override(profile_path: &Path) -> Result<()> {
-    // Use git config -f to read values directly from the file, bypassing
-    // any environment or global overrides for consistency.
-    let output = Command::new("git")
-        .args(&["config", "-f", &profile_path.to_string_lossy(), "--list"])
-        .output()
-        .with_context(|| format!("Failed to read profile config from {:?}", profile_path))?;
+// The part of the shared config that binds an identity: profile includes,
+// blacklisted sections and keys, and URL rewrites to host aliases.
+struct SharedBinding {
+    entries: Vec<gitconfig::Entry>,
+    includes: Vec<String>,
+    sections: Vec<String>,
+    keys: Vec<String>,
+}
+
+fn shared_binding(config: &NajConfig, shared: &GitConfig) -> Result<SharedBinding> {
+    let profile_dir = get_profile_dir(config)?;
+    let rewrites = hosts::alias_rewrite_sections(shared, &hosts::managed_aliases(config)?);
+    let mut binding = SharedBinding {
+        entries: Vec::new(),
+        includes: Vec::new(),
+        sections: Vec::new(),
+        keys: Vec::new(),
+    };
+
+    for entry in shared.entries() {
+        let key = entry.key();
+        let section = match &entry.subsection {
+            Some(sub) => format!("{}.{}", entry.section, sub),
+            None => entry.section.clone(),
+        };
+        if key == "include.path" {
+            let val = entry.value_or_true();
+            if !is_profile_include(val, &profile_dir) {
+                continue;
+            }
+            binding.includes.push(val.to_string());
+        } else if sanitizer::BLACKLIST_SECTIONS.contains(&entry.section.as_str())
+            || rewrites.contains(&section)
+        {
+            if !binding.sections.contains(&section) {
+                binding.sections.push(section);
+            }
+        } else if let Some(name) = sanitizer::BLACKLIST_KEYS
+            .iter()
+            .find(|name| name.eq_ignore_ascii_case(&key))
+        {
+            if !binding.keys.iter().any(|k| k == name) {
+                binding.keys.push(name.to_string());
+            }
+        } else {
+            continue;
+        }
+        binding.entries.push(entry.clone());
+    }
+    Ok(binding)
+}
 
+// Work trees of every worktree of the repository, the main one included.
+// Bare main repositories and worktrees whose directory is gone are skipped.
+fn list_worktrees(repo: &Path) -> Result<Vec<PathBuf>> {
+    let output = git_in(repo)
+        .args(["worktree", "list", "--porcelain"])
+        .output()
+        .context("Failed to list worktrees")?;
     if !output.status.success() {
-        return Err(anyhow!(
-            "Git config read failed: {}",
+        bail!(
+            "Failed to list worktrees: {}",
             String::from_utf8_lossy(&output.stderr).trim()
-        ));
+        );
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut worktrees = Vec::new();
+    for block in String::from_utf8_lossy(&output.stdout).split("\n\n") {
+        let mut lines = block.lines();
+        let Some(path) = lines.next().and_then(|l| l.strip_prefix("worktree ")) else {
+            continue;
+        };
+        if lines.any(|l| l == "bare") || !Path::new(path).is_dir() {
+            continue;
+        }
+        worktrees.push(PathBuf::from(path));
+    }
+    Ok(worktrees)
+}
+
+// Locates and removes existing Naj profile inclusions from the local git config
+// to prevent configuration pollution or conflicts, together with the URL
+// rewrites to host aliases a previous override switch copied in.
+fn clean_existing_profiles(config: &NajConfig, repo: &Path, scope: &str) -> Result<()> {
+    for val in local_profile_includes(repo, &get_profile_dir(config)?)? {
+        let mut cmd = git_in(repo);
+        cmd.args(["config", scope, "--unset", "include.path", &val]);
+        run_cleanup(&mut cmd, &format!("include.path '{}'", val))?;
+    }
+
+    let config_path = switch_config_path(repo)?;
+    if config_path.exists() {
+        let local = GitConfig::load(&config_path)?;
+        for section in hosts::alias_rewrite_sections(&local, &hosts::managed_aliases(config)?) {
+            let mut cmd = git_in(repo);
+            cmd.args(["config", scope, "--remove-section", &section]);
+            run_cleanup(&mut cmd, &format!("section '{}'", section))?;
+        }
+    }
+    Ok(())
+}
+
+fn apply_profile_override(repo: &Path, scope: &str, profile_path: &Path) -> Result<()> {
+    // Values are read directly from the file, bypassing any environment or
+    // global overrides for consistency.
+    let entries = read_profile_config(profile_path)?;
 
     // For Override strategies, we manually inject values into the local config
-    // to strictly enforce the profile's settings.
-    for line in stdout.lines() {
-        if let Some((key, value)) = line.split_once('=') {
-            let mut cmd = Command::new("git");
-            cmd.args(&["config", "--local", key, value]);
-            run_command(&mut cmd)?;
+    // to strictly enforce the profile's settings. The first value of a key
+    // replaces whatever is there; further values are added (multivars).
+    let mut seen: Vec<&str> = Vec::new();
+    for (key, value) in &entries {
+        let mut cmd = git_in(repo);
+        if seen.contains(&key.as_str()) {
+            cmd.args(["config", scope, "--add", key, value]);
+        } else {
+            seen.push(key);
+            cmd.args(["config", scope, "--replace-all", key, value]);
         }
+        run_command(&mut cmd)?;
     }
 
     Ok(())
 }
 
-fn run_exec(config: &NajConfig, profile_id: &str, args: &[String]) -> Result<()> {
+fn run_exec(
+    config: &NajConfig,
+    repo: &Path,
+    profile_id: &ProfileId,
+    args: &[String],
+) -> Result<()> {
     let profile_path = get_profile_path(config, profile_id)?;
-    let mut cmd = Command::new("git");
+    let mut cmd = git_with_profile(repo, &profile_path);
+    cmd.args(args);
+    run_command(&mut cmd)
+}
+
+// A git command in `repo` that runs as the profile, whatever the repository
+// and global config say.
+fn git_with_profile(repo: &Path, profile_path: &Path) -> Command {
+    let mut cmd = git_in(repo);
 
     // 1. Sensitize defaults to prevent leakages if not explicitly covered by the profile
     for (k, v) in sanitizer::BLIND_INJECTIONS {
-        cmd.args(&["-c", &format!("{}={}", k, v)]);
+        cmd.args(["-c", &format!("{}={}", k, v)]);
     }
 
     // 2. Attach profile via git's native include path for most operations
-    cmd.args(&[
+    cmd.args([
         "-c",
         &format!("include.path={}", profile_path.to_string_lossy()),
     ]);
 
     // 3. Force-inject profile values to ensure they override any local config
     // that might conflict with the base inclusion.
-    if let Ok(entries) = read_profile_config(&profile_path) {
+    if let Ok(entries) = read_profile_config(profile_path) {
+        // GIT_SSH_COMMAND and GIT_SSH would win over the profile's key
+        if entries
+            .iter()
+            .any(|(k, _)| k.eq_ignore_ascii_case("core.sshCommand"))
+        {
+            cmd.env_remove("GIT_SSH_COMMAND").env_remove("GIT_SSH");
+        }
         for (k, v) in entries {
-            cmd.args(&["-c", &format!("{}={}", k, v)]);
+            cmd.args(["-c", &format!("{}={}", k, v)]);
         }
     }
-
-    cmd.args(args);
-    run_command(&mut cmd)
+    cmd
 }
 
-fn run_switch(config: &NajConfig, profile_id: &str, force: bool) -> Result<()> {
-    let status = Command::new("git")
-        .args(&["rev-parse", "--is-inside-work-tree"])
+// Succeeds for work trees and bare repositories alike. `rev-parse --git-dir`
+// honours GIT_DIR and GIT_WORK_TREE, so the git dir need not be `repo/.git`.
+pub fn ensure_git_repo(repo: &Path) -> Result<()> {
+    let status = git_in(repo)
+        .args(["rev-parse", "--git-dir"])
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
         .status();
 
     let is_git_repo = status.map(|s| s.success()).unwrap_or(false);
     if !is_git_repo {
+        return Err(anyhow!("Not a git repository: {}", repo.display()));
+    }
+    Ok(())
+}
+
+// Absolute path of the repository's git dir.
+pub fn git_dir(repo: &Path) -> Result<PathBuf> {
+    let output = git_in(repo)
+        .args(["rev-parse", "--absolute-git-dir"])
+        .output()
+        .context("Failed to locate git dir")?;
+    if !output.status.success() {
         return Err(anyhow!("Not a git repository"));
     }
+    Ok(PathBuf::from(
+        String::from_utf8_lossy(&output.stdout).trim_end_matches('\n'),
+    ))
+}
+
+// Switches the repository at `repo` (its work tree or git dir) to a profile.
+pub fn run_switch(
+    config: &NajConfig,
+    repo: &Path,
+    profile_id: &ProfileId,
+    base_strategy: SwitchStrategy,
+    force: bool,
+    dry_run: Option<PlanFormat>,
+) -> Result<()> {
+    ensure_git_repo(repo)?;
 
     let profile_path = get_profile_path(config, profile_id)?;
     let abs_profile_path = if profile_path.is_absolute() {
@@ -171,12 +738,10 @@ fn run_switch(config: &NajConfig, profile_id: &str, force: bool) -> Result<()> {
     };
 
     // 1. Resolve Effective Strategy
-    let base_strategy = config.strategies.switch;
-    let effective_strategy = match (force, base_strategy) {
-        (true, SwitchStrategy::IncludeSoft) => SwitchStrategy::IncludeHard,
-        (true, SwitchStrategy::OverrideSoft) => SwitchStrategy::OverrideHard,
-        (false, s) => s,
-        _ => SwitchStrategy::IncludeHard, // Fallback
+    let effective_strategy = if force {
+        base_strategy.hardened()
+    } else {
+        base_strategy
     };
 
     // Log the resolved strategy for trace visibility in debug mode
@@ -187,6 +752,89 @@ fn run_switch(config: &NajConfig, profile_id: &str, force: bool) -> Result<()> {
         effective_strategy
     );
 
+    if let Some(format) = dry_run {
+        let plan = SwitchPlan::build(
+            config,
+            repo,
+            profile_id,
+            &abs_profile_path,
+            effective_strategy,
+        )?;
+        return plan.print(format);
+    }
+
+    if is_mocking() {
+        apply_switch(config, repo, &abs_profile_path, effective_strategy)?;
+        println!("Switched to profile '{}'", profile_id);
+        return Ok(());
+    }
+
+    // 2. Apply all changes as one transaction: if any step or the final
+    // verification fails, the local config is put back exactly as it was.
+    let snapshot = ConfigSnapshot::take(&switch_config_path(repo)?)?;
+    let outcome = apply_switch(config, repo, &abs_profile_path, effective_strategy)
+        .and_then(|_| verify_switch(config, repo, &abs_profile_path, effective_strategy));
+
+    if let Err(err) = outcome {
+        snapshot
+            .restore()
+            .context("Switch failed and the local config could not be restored")?;
+        return Err(err.context(format!(
+            "Failed to switch to profile '{}'; the local config was left unchanged",
+            profile_id
+        )));
+    }
+
+    println!("Switched to profile '{}'", profile_id);
+
+    // Keep the previous state so the switch can be undone with `naj undo`
+    let action = format!("switch to '{}' ({})", profile_id, effective_strategy);
+    if let Err(err) = history::record(config, &snapshot, &action) {
+        eprintln!(
+            "⚠️  Could not save the previous config to history: {:#}",
+            err
+        );
+    }
+
+    warn_if_dirty_config(config, repo, &abs_profile_path, effective_strategy)?;
+    gpg::warn_if_key_missing(profile_id, &abs_profile_path)?;
+
+    Ok(())
+}
+
+// Runs a `git config` cleanup command where "nothing to remove" is not an error.
+fn run_cleanup(cmd: &mut Command, what: &str) -> Result<()> {
+    if is_mocking() {
+        eprintln!("[DRY-RUN] {:?}", cmd);
+        return Ok(());
+    }
+
+    naj_debug!("Executing sanitize: {:?}", cmd);
+    let output = cmd
+        .output()
+        .with_context(|| format!("Failed to attempt removing {}", what))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // Exit code 5 means the key was not set and "no such section" that the
+        // section is absent: both are expected if the config is already clean.
+        // Older gits report the same conditions with exit code 1.
+        let is_benign =
+            matches!(output.status.code(), Some(1) | Some(5)) || stderr.contains("no such section");
+
+        if !is_benign {
+            return Err(anyhow!("Failed to remove {}: {}", what, stderr.trim()));
+        }
+    }
+    Ok(())
+}
+
+fn apply_switch(
+    config: &NajConfig,
+    repo: &Path,
+    abs_profile_path: &Path,
+    effective_strategy: SwitchStrategy,
+) -> Result<()> {
     // Hard strategies require a clean slate to ensure security and privacy
     let should_sanitize = matches!(
         effective_strategy,
@@ -195,92 +843,154 @@ fn run_switch(config: &NajConfig, profile_id: &str, force: bool) -> Result<()> {
 
     naj_debug!("Should Sanitize? {}", should_sanitize);
 
+    // `--local`, or `--worktree` when profiles are bound per worktree
+    let scope = switch_scope(repo)?;
+
     if should_sanitize {
         // Remove sections
-        let sections = sanitizer::BLACKLIST_SECTIONS;
-        for section in sections {
-            let mut cmd = Command::new("git");
+        for section in sanitizer::BLACKLIST_SECTIONS {
+            let mut cmd = git_in(repo);
 
             // Explicitly target local config and dereference section name for type safety
-            cmd.args(&["config", "--local", "--remove-section", *section]);
-
-            if is_mocking() {
-                eprintln!("[DRY-RUN] {:?}", cmd);
-            } else {
-                naj_debug!("Executing sanitize: {:?}", cmd);
-                let output = cmd
-                    .output()
-                    .context(format!("Failed to attempt removing section {}", section))?;
-
-                // Exit code 1 means "section not found" (benign).
-                // We only care about other errors.
-                if !output.status.success() {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    // Git exit code 1 usually means a section or key was not found,
-                    // which is expected if the config is already clean.
-                    let is_benign =
-                        output.status.code() == Some(1) || stderr.contains("no such section");
-
-                    if !is_benign {
-                        return Err(anyhow!(
-                            "Failed to remove section '{}': {}",
-                            section,
-                            stderr.trim()
-                        ));
-                    }
-                }
+            cmd.args(["config", scope, "--remove-section", *section]);
+            run_cleanup(&mut cmd, &format!("section '{}'", section))?;
+        }
+        let config_path = switch_config_path(repo)?;
+        if config_path.exists() {
+            for section in sanitizer::blacklisted_subsections(&GitConfig::load(&config_path)?) {
+                let mut cmd = git_in(repo);
+                cmd.args(["config", scope, "--remove-section", &section]);
+                run_cleanup(&mut cmd, &format!("section '{}'", section))?;
             }
         }
 
         // Wipe 'include' section to prevent residual profile links in Hard mode
-        let mut cmd = Command::new("git");
-        cmd.args(&["config", "--local", "--remove-section", "include"]);
-        if is_mocking() {
-            eprintln!("[DRY-RUN] {:?}", cmd);
-        } else {
-            // Success is not critical here as a missing section is already 'clean'
-            let _ = cmd.output();
-        }
+        let mut cmd = git_in(repo);
+        cmd.args(["config", scope, "--remove-section", "include"]);
+        run_cleanup(&mut cmd, "section 'include'")?;
 
         // Unset keys
-        let keys = sanitizer::BLACKLIST_KEYS;
-        for key in keys {
-            let mut cmd = Command::new("git");
-            cmd.args(&["config", "--local", "--unset-all", *key]); // 👈 deref here too
-
-            if is_mocking() {
-                eprintln!("[DRY-RUN] {:?}", cmd);
-            } else {
-                let _ = cmd.output();
-            }
+        for key in sanitizer::BLACKLIST_KEYS {
+            let mut cmd = git_in(repo);
+            cmd.args(["config", scope, "--unset-all", *key]);
+            run_cleanup(&mut cmd, &format!("key '{}'", key))?;
         }
     }
 
     // Clean orphaned Naj profile references before applying a new one
-    let profiles_dir = get_profile_dir(config)?;
-    clean_existing_profiles(&profiles_dir)?;
+    clean_existing_profiles(config, repo, scope)?;
 
     match effective_strategy {
         SwitchStrategy::IncludeSoft | SwitchStrategy::IncludeHard => {
             let path_str = abs_profile_path.to_string_lossy();
-            let mut cmd = Command::new("git");
-            cmd.args(&["config", "--local", "--add", "include.path", &path_str]);
+            let mut cmd = git_in(repo);
+            cmd.args(["config", scope, "--add", "include.path", &path_str]);
             run_command(&mut cmd)?;
         }
         SwitchStrategy::OverrideSoft | SwitchStrategy::OverrideHard => {
-            apply_profile_override(&abs_profile_path)?;
+            apply_profile_override(repo, scope, abs_profile_path)?;
         }
     }
-    println!("Switched to profile '{}'", profile_id);
+    Ok(())
+}
 
-    warn_if_dirty_config(profile_id, effective_strategy)?;
+// Checks that the local config now carries the profile, and that the
+// identity git will actually use is the profile's one.
+fn verify_switch(
+    config: &NajConfig,
+    repo: &Path,
+    abs_profile_path: &Path,
+    effective_strategy: SwitchStrategy,
+) -> Result<()> {
+    let entries = read_profile_config(abs_profile_path)?;
 
+    match effective_strategy {
+        SwitchStrategy::IncludeSoft | SwitchStrategy::IncludeHard => {
+            let path_str = abs_profile_path.to_string_lossy();
+            let includes = local_profile_includes(repo, &get_profile_dir(config)?)?;
+            if !includes.iter().any(|p| *p == path_str) {
+                bail!(
+                    "Verification failed: include.path for {:?} is missing",
+                    path_str
+                );
+            }
+        }
+        SwitchStrategy::OverrideSoft | SwitchStrategy::OverrideHard => {
+            let local = GitConfig::load(&switch_config_path(repo)?)?;
+            let mut keys: Vec<&str> = entries.iter().map(|(k, _)| k.as_str()).collect();
+            keys.dedup();
+            for key in keys {
+                let expected: Vec<&str> = entries
+                    .iter()
+                    .filter(|(k, _)| k == key)
+                    .map(|(_, v)| v.as_str())
+                    .collect();
+                let actual = local.get_all(key);
+                if expected != actual {
+                    bail!(
+                        "Verification failed: local {} is {:?}, expected {:?}",
+                        key,
+                        actual,
+                        expected
+                    );
+                }
+            }
+        }
+    }
+
+    // Soft includes deliberately leave local overrides in place (they are
+    // reported as dirty config instead), so only strategies that guarantee
+    // the profile wins are checked against the effective identity.
+    if effective_strategy == SwitchStrategy::IncludeSoft {
+        return Ok(());
+    }
+    for key in ["user.name", "user.email"] {
+        let Some((_, expected)) = entries
+            .iter()
+            .rev()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        else {
+            continue;
+        };
+        let actual = effective_value(repo, key)?;
+        if actual.as_deref() != Some(expected.as_str()) {
+            bail!(
+                "Verification failed: effective {} is '{}' but the profile sets '{}'",
+                key,
+                actual.as_deref().unwrap_or(""),
+                expected
+            );
+        }
+    }
     Ok(())
 }
 
-fn run_setup(config: &NajConfig, profile_id: &str, args: &[String]) -> Result<()> {
-    // Execute the base command (init/clone) before applying Naj customization
-    let mut cmd = Command::new("git");
+pub fn effective_value(repo: &Path, key: &str) -> Result<Option<String>> {
+    let output = git_in(repo)
+        .args(["config", "--get", key])
+        .output()
+        .with_context(|| format!("Failed to read effective value of {}", key))?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(
+        String::from_utf8_lossy(&output.stdout)
+            .trim_end_matches('\n')
+            .to_string(),
+    ))
+}
+
+fn run_setup(
+    config: &NajConfig,
+    repo: &Path,
+    profile_id: &ProfileId,
+    args: &[String],
+    strategy: SwitchStrategy,
+    force: bool,
+) -> Result<()> {
+    // Execute the base command (init/clone) as the profile already, so a clone
+    // fetches with the profile's core.sshCommand and credentials
+    let mut cmd = git_with_profile(repo, &get_profile_path(config, profile_id)?);
     cmd.args(args);
     run_command(&mut cmd)?;
 
@@ -290,100 +1000,286 @@ fn run_setup(config: &NajConfig, profile_id: &str, args: &[String]) -> Result<()
 
     let command = &args[0];
 
-    // 2. Switch context if needed
-    if command == "init" {
-        // Init happens in current dir
-        run_switch(config, profile_id, false)?;
+    // 2. Locate the repository git created; paths are relative to the
+    // directory it runs in
+    let target_dir = if command == "init" {
+        // `git init [<directory>]`, also with --bare: the repository is the
+        // directory itself
+        let init = parse_git_options(args, INIT_VALUE_OPTIONS, INIT_SHORT_VALUE_OPTIONS);
+        repo.join(init.positionals.first().copied().unwrap_or("."))
     } else if command == "clone" {
-        // Parse target directory from clone arguments while ignoring flags (e.g., --depth)
-        let mut url = None;
-        let mut explicit_dir = None;
+        let clone = parse_clone_args(args);
+        repo.join(match (clone.dir, clone.url) {
+            (Some(dir), _) => PathBuf::from(dir),
+            (None, Some(url)) => clone_dir_name(url, clone.bare),
+            (None, None) => bail!("No repository URL given to clone"),
+        })
+    } else {
+        return Ok(());
+    };
 
-        // Skip 'clone'
-        for arg in args.iter().skip(1) {
-            if arg.starts_with('-') {
-                continue;
+    // Nothing was created when the git command was only printed
+    if is_mocking() {
+        return Ok(());
+    }
+    if !target_dir.is_dir() {
+        bail!(
+            "Created, but the new repository was not found at {:?}; run `naj {}` inside it",
+            target_dir,
+            profile_id
+        );
+    }
+    run_switch(config, &target_dir, profile_id, strategy, force, None)
+}
+
+// `git init` options whose value is the next argument unless written as
+// `--option=value`.
+const INIT_VALUE_OPTIONS: &[&str] = &[
+    "--template",
+    "--separate-git-dir",
+    "--object-format",
+    "--ref-format",
+    "--initial-branch",
+];
+
+// Short form of the above: -b.
+const INIT_SHORT_VALUE_OPTIONS: &[char] = &['b'];
+
+// `git clone` options whose value is the next argument unless written as
+// `--option=value`.
+const CLONE_VALUE_OPTIONS: &[&str] = &[
+    "--origin",
+    "--branch",
+    "--revision",
+    "--upload-pack",
+    "--reference",
+    "--reference-if-able",
+    "--separate-git-dir",
+    "--depth",
+    "--shallow-since",
+    "--shallow-exclude",
+    "--config",
+    "--template",
+    "--jobs",
+    "--filter",
+    "--server-option",
+    "--bundle-uri",
+    "--ref-format",
+];
+
+// Short forms of the above: -o, -b, -u, -c and -j.
+const CLONE_SHORT_VALUE_OPTIONS: &[char] = &['o', 'b', 'u', 'c', 'j'];
+
+// The arguments of a git subcommand, split by its option grammar.
+struct GitOptions<'a> {
+    positionals: Vec<&'a str>,
+    // Long options without a value, in order, e.g. `--bare`
+    flags: Vec<&'a str>,
+}
+
+// Splits `args` (the subcommand name first) the way git's option parser does,
+// so that option values (`-b main`, `--reference <path>`) are never taken for
+// positional arguments. `value_options` and `short_value_options` name the
+// options that take the next argument as their value.
+fn parse_git_options<'a>(
+    args: &'a [String],
+    value_options: &[&str],
+    short_value_options: &[char],
+) -> GitOptions<'a> {
+    let mut parsed = GitOptions {
+        positionals: Vec::new(),
+        flags: Vec::new(),
+    };
+
+    // Skip the subcommand
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        let arg = arg.as_str();
+        if arg == "--" {
+            parsed.positionals.extend(rest.by_ref().map(String::as_str));
+            break;
+        }
+        if arg.starts_with("--") {
+            if value_options.contains(&arg) {
+                rest.next();
+            } else {
+                parsed.flags.push(arg);
             }
-            if url.is_none() {
-                url = Some(arg);
-            } else if explicit_dir.is_none() {
-                explicit_dir = Some(arg);
+            continue;
+        }
+        if let Some(cluster) = arg.strip_prefix('-').filter(|c| !c.is_empty()) {
+            // Short options can be bundled (`-qb main`); a value option takes
+            // the rest of the cluster (`-bmain`) or else the next argument.
+            if let Some(pos) = cluster.find(short_value_options) {
+                if pos + 1 == cluster.len() {
+                    rest.next();
+                }
             }
+            continue;
         }
+        parsed.positionals.push(arg);
+    }
+    parsed
+}
 
-        let target_dir = if let Some(dir) = explicit_dir {
-            PathBuf::from(dir)
-        } else if let Some(u) = url {
-            extract_basename(u)
-        } else {
-            // Default name if it cannot be inferred from the URL
-            PathBuf::from("repo")
-        };
+// What `git clone` was asked to create.
+struct CloneArgs<'a> {
+    url: Option<&'a str>,
+    dir: Option<&'a str>,
+    // `--bare` or `--mirror`: the new repository has no work tree
+    bare: bool,
+}
 
-        if target_dir.exists() && target_dir.is_dir() {
-            std::env::set_current_dir(&target_dir)?;
-            run_switch(config, profile_id, false)?;
-        }
+fn parse_clone_args(args: &[String]) -> CloneArgs<'_> {
+    let parsed = parse_git_options(args, CLONE_VALUE_OPTIONS, CLONE_SHORT_VALUE_OPTIONS);
+    let bare = parsed
+        .flags
+        .iter()
+        .rev()
+        .find_map(|flag| match *flag {
+            "--bare" | "--mirror" => Some(true),
+            "--no-bare" => Some(false),
+            _ => None,
+        })
+        .unwrap_or(false);
+
+    CloneArgs {
+        url: parsed.positionals.first().copied(),
+        dir: parsed.positionals.get(1).copied(),
+        bare,
     }
+}
 
-    Ok(())
+// The value git would use for `key` from a profile, with empty values counting
+// as unset.
+pub fn profile_value(profile_path: &Path, key: &str) -> Result<Option<String>> {
+    Ok(read_profile_config(profile_path)?
+        .into_iter()
+        .rev()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v)
+        .filter(|v| !v.is_empty()))
 }
 
-fn read_profile_config(profile_path: &Path) -> Result<Vec<(String, String)>> {
-    let output = Command::new("git")
-        .args(&["config", "-f", &profile_path.to_string_lossy(), "--list"])
-        .output()
-        .with_context(|| format!("Failed to read profile config from {:?}", profile_path))?;
+// Reads every key of a profile (following its includes) in file order.
+// Include directives themselves are dropped since their targets are inlined.
+// A key set again in a later file (e.g. a child profile after the parent it
+// includes) replaces the earlier file's values; multivars within one file stay.
+pub fn read_profile_config(profile_path: &Path) -> Result<Vec<(String, String)>> {
+    Ok(read_profile_entries(profile_path)?
+        .into_iter()
+        .map(|(_, k, v)| (k, v))
+        .collect())
+}
 
-    if !output.status.success() {
-        return Err(anyhow!("Git config read failed"));
-    }
+// Same as `read_profile_config`, with the file each value comes from.
+pub fn read_profile_entries(profile_path: &Path) -> Result<Vec<(PathBuf, String, String)>> {
+    let entries = gitconfig::load_with_includes(profile_path, None)
+        .with_context(|| format!("Failed to read profile config from {:?}", profile_path))?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut entries = Vec::new();
-    for line in stdout.lines() {
-        if let Some((key, value)) = line.split_once('=') {
-            entries.push((key.to_string(), value.to_string()));
+    let mut resolved: Vec<(PathBuf, String, String)> = Vec::new();
+    for (origin, e) in entries {
+        if e.name == "path" && (e.section == "include" || e.section == "includeif") {
+            continue;
         }
+        let key = e.key();
+        resolved.retain(|(o, k, _)| !(k.eq_ignore_ascii_case(&key) && *o != origin));
+        let value = e.value_or_true().to_string();
+        resolved.push((origin, key, value));
     }
-    Ok(entries)
+    Ok(resolved)
 }
 
-fn extract_basename(url: &str) -> PathBuf {
-    let mut s = url.trim_end_matches('/');
-    if s.ends_with(".git") {
-        s = &s[..s.len() - 4];
+// The directory `git clone` creates when none is given. Like git, this is the
+// last component of the URL or path without a trailing `/.git`, `.git` or
+// `.bundle`, with `.git` appended for bare clones.
+fn clone_dir_name(url: &str, bare: bool) -> PathBuf {
+    let s = url.trim_end_matches('/');
+    let s = s.strip_suffix("/.git").unwrap_or(s).trim_end_matches('/');
+    let s = s
+        .strip_suffix(".git")
+        .or_else(|| s.strip_suffix(".bundle"))
+        .unwrap_or(s);
+    // `host:path` URLs have no slash before the name
+    let name = s
+        .rsplit(['/', ':'])
+        .next()
+        .filter(|name| !name.is_empty())
+        .unwrap_or("repo");
+    if bare {
+        PathBuf::from(format!("{}.git", name))
+    } else {
+        PathBuf::from(name)
     }
-    Path::new(s)
-        .file_name()
-        .map(PathBuf::from)
-        .unwrap_or_else(|| PathBuf::from("repo"))
 }
 
-fn warn_if_dirty_config(_profile_id: &str, strategy: SwitchStrategy) -> Result<()> {
-    // Check for local configuration that might leak identity or signing info
-    // when using an 'Include' strategy.
-    let config_path = Path::new(".git/config");
-    if config_path.exists() {
-        let content = std::fs::read_to_string(config_path)?;
-        let check_user_block = matches!(
-            strategy,
-            SwitchStrategy::IncludeSoft | SwitchStrategy::IncludeHard
-        );
-        let mut is_dirty = false;
-        if check_user_block && (content.contains("[user]") || content.contains("[author]")) {
-            is_dirty = true;
+fn warn_if_dirty_config(
+    config: &NajConfig,
+    repo: &Path,
+    profile_path: &Path,
+    strategy: SwitchStrategy,
+) -> Result<()> {
+    // Under Override strategies the profile's own keys live in the local config
+    // by design, so only keys the profile does not provide count as dirty.
+    let is_override = matches!(
+        strategy,
+        SwitchStrategy::OverrideSoft | SwitchStrategy::OverrideHard
+    );
+    let findings = dirty_config_findings(config, repo, is_override.then_some(profile_path))?;
+
+    if !findings.is_empty() {
+        println!("\n⚠️  WARNING: Dirty Local Config Detected!");
+        println!("The local config still sets keys that can leak into this identity:");
+        for key in findings {
+            println!("  - {}", key);
         }
-        if content.contains("[gpg]")
-            || content.contains("sshCommand")
-            || content.contains("gpgsign")
+        println!("Run with -f to remove them.");
+    }
+    Ok(())
+}
+
+// Keys in the local config that might leak identity or signing info, i.e.
+// exactly what a Hard switch removes. Keys of `override_profile`, whose values
+// were injected on purpose, do not count. With per-worktree config only the
+// worktree's own file is checked, since that is the one `-f` cleans.
+pub fn dirty_config_findings(
+    config: &NajConfig,
+    repo: &Path,
+    override_profile: Option<&Path>,
+) -> Result<Vec<String>> {
+    let profile_keys: Vec<String> = match override_profile {
+        Some(profile_path) => read_profile_config(profile_path)?
+            .into_iter()
+            .map(|(k, _)| k.to_ascii_lowercase())
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let config_path = switch_config_path(repo)?;
+    if !config_path.exists() {
+        return Ok(Vec::new());
+    }
+    let local = GitConfig::load(&config_path)?;
+
+    let rewrites = hosts::alias_rewrite_sections(&local, &hosts::managed_aliases(config)?);
+    let mut findings = Vec::new();
+    for entry in local.entries() {
+        let key = entry.key();
+        let in_rewrite = entry
+            .subsection
+            .as_ref()
+            .is_some_and(|sub| rewrites.contains(&format!("{}.{}", entry.section, sub)));
+        let is_sensitive = in_rewrite
+            || sanitizer::BLACKLIST_SECTIONS.contains(&entry.section.as_str())
+            || sanitizer::BLACKLIST_KEYS
+                .iter()
+                .any(|k| k.eq_ignore_ascii_case(&key));
+        if is_sensitive
+            && !profile_keys.contains(&key.to_ascii_lowercase())
+            && !findings.contains(&key)
         {
-            is_dirty = true;
-        }
-        if is_dirty {
-            println!("\n⚠️  WARNING: Dirty Local Config Detected!");
-            // ...
+            findings.push(key);
         }
     }
-    Ok(())
+    Ok(findings)
 }
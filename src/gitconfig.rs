@@ -0,0 +1,685 @@
+// Native reader/writer for git's config file format.
+//
+// Parsing follows git's own rules (config.c): case-insensitive section and key
+// names, case-sensitive quoted subsections, multi-valued keys, quoting, escape
+// sequences, line continuations and `#`/`;` comments. Every parsed item keeps
+// its original text, so editing a file only rewrites the lines that changed.
+
+use crate::utils::{expand_path, glob_match};
+use anyhow::{anyhow, bail, Context, Result};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Maximum include nesting, matching git's own limit.
+const MAX_INCLUDE_DEPTH: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    // Lowercased, as git compares it case-insensitively
+    pub section: String,
+    // Case is preserved for quoted subsections (`[url "git@host:"]`)
+    pub subsection: Option<String>,
+    // Lowercased, as git compares it case-insensitively
+    pub name: String,
+    // `None` for bare keys written without `=`, which git treats as true
+    pub value: Option<String>,
+}
+
+impl Entry {
+    // The key in the same form `git config --list` prints it.
+    pub fn key(&self) -> String {
+        match &self.subsection {
+            Some(sub) => format!("{}.{}.{}", self.section, sub, self.name),
+            None => format!("{}.{}", self.section, self.name),
+        }
+    }
+
+    pub fn value_or_true(&self) -> &str {
+        self.value.as_deref().unwrap_or("true")
+    }
+
+    fn matches(&self, key: &Key) -> bool {
+        self.section == key.section && self.subsection == key.subsection && self.name == key.name
+    }
+}
+
+// A parsed `section[.subsection].name` key. Section and name are lowercased
+// for matching; the spelling given by the caller is kept for writing.
+struct Key {
+    section: String,
+    subsection: Option<String>,
+    name: String,
+    spelling: (String, String),
+}
+
+impl Key {
+    fn parse(key: &str) -> Result<Key> {
+        let (section, rest) = key
+            .split_once('.')
+            .ok_or_else(|| anyhow!("Invalid config key '{}': missing section", key))?;
+        let (subsection, name) = match rest.rsplit_once('.') {
+            Some((sub, name)) => (Some(sub.to_string()), name),
+            None => (None, rest),
+        };
+        if section.is_empty() || name.is_empty() {
+            bail!("Invalid config key '{}'", key);
+        }
+        Ok(Key {
+            section: section.to_ascii_lowercase(),
+            subsection,
+            name: name.to_ascii_lowercase(),
+            spelling: (section.to_string(), name.to_string()),
+        })
+    }
+}
+
+// Items without `raw` text were added programmatically and are written in
+// canonical form, using `spelling` for the section or key name.
+enum Item {
+    Section {
+        section: String,
+        subsection: Option<String>,
+        raw: Option<String>,
+        spelling: String,
+    },
+    Entry {
+        entry: Entry,
+        raw: Option<String>,
+        spelling: String,
+    },
+    // Comments; surrounding whitespace is kept in the following item's raw text
+    Comment(String),
+}
+
+#[derive(Default)]
+pub struct GitConfig {
+    items: Vec<Item>,
+    // Text after the last item (usually the final newline)
+    tail: String,
+}
+
+impl GitConfig {
+    pub fn parse(text: &str) -> Result<GitConfig> {
+        Parser::new(text).parse()
+    }
+
+    pub fn load(path: &Path) -> Result<GitConfig> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {:?}", path))?;
+        Self::parse(&text).with_context(|| format!("Failed to parse config file {:?}", path))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_string())
+            .with_context(|| format!("Failed to write config file {:?}", path))
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Entry { entry, .. } => Some(entry),
+            _ => None,
+        })
+    }
+
+    // Last value of `key`, which is the one git uses.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.get_all(key).pop()
+    }
+
+    pub fn get_all(&self, key: &str) -> Vec<&str> {
+        let Ok(key) = Key::parse(key) else {
+            return Vec::new();
+        };
+        self.entries()
+            .filter(|e| e.matches(&key))
+            .map(|e| e.value_or_true())
+            .collect()
+    }
+
+    // Replaces every value of `key` with a single value, like `--replace-all`.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let parsed = Key::parse(key)?;
+        let last = self
+            .items
+            .iter()
+            .rposition(|item| matches!(item, Item::Entry { entry, .. } if entry.matches(&parsed)));
+        let Some(last) = last else {
+            return self.add(key, value);
+        };
+
+        if let Item::Entry {
+            entry,
+            raw,
+            spelling,
+        } = &mut self.items[last]
+        {
+            entry.value = Some(value.to_string());
+            *raw = None;
+            *spelling = parsed.spelling.1.clone();
+        }
+        let mut index = 0;
+        self.items.retain(|item| {
+            let keep = index == last
+                || !matches!(item, Item::Entry { entry, .. } if entry.matches(&parsed));
+            index += 1;
+            keep
+        });
+        Ok(())
+    }
+
+    // Appends a value to `key`, keeping existing values (multivar).
+    pub fn add(&mut self, key: &str, value: &str) -> Result<()> {
+        let parsed = Key::parse(key)?;
+        let entry = Item::Entry {
+            entry: Entry {
+                section: parsed.section.clone(),
+                subsection: parsed.subsection.clone(),
+                name: parsed.name.clone(),
+                value: Some(value.to_string()),
+            },
+            raw: None,
+            spelling: parsed.spelling.1,
+        };
+
+        // Insert after the last item of the last block for this section
+        let mut in_block = false;
+        let mut insert_at = None;
+        for (i, item) in self.items.iter().enumerate() {
+            match item {
+                Item::Section {
+                    section,
+                    subsection,
+                    ..
+                } => {
+                    in_block = *section == parsed.section && *subsection == parsed.subsection;
+                    if in_block {
+                        insert_at = Some(i + 1);
+                    }
+                }
+                Item::Entry { .. } if in_block => insert_at = Some(i + 1),
+                _ => {}
+            }
+        }
+
+        match insert_at {
+            Some(i) => self.items.insert(i, entry),
+            None => {
+                self.items.push(Item::Section {
+                    section: parsed.section,
+                    subsection: parsed.subsection,
+                    raw: None,
+                    spelling: parsed.spelling.0,
+                });
+                self.items.push(entry);
+            }
+        }
+        Ok(())
+    }
+
+    // Removes all blocks of `section` (with the given subsection) and their
+    // contents, like `git config --remove-section`. Returns whether any existed.
+    pub fn remove_section(&mut self, section: &str, subsection: Option<&str>) -> bool {
+        let section = section.to_ascii_lowercase();
+        let mut removing = false;
+        let mut removed = false;
+        self.items.retain(|item| {
+            if let Item::Section {
+                section: s,
+                subsection: sub,
+                ..
+            } = item
+            {
+                removing = *s == section && sub.as_deref() == subsection;
+                removed |= removing;
+            }
+            !removing
+        });
+        removed
+    }
+}
+
+impl fmt::Display for GitConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        for item in &self.items {
+            match item {
+                Item::Section { raw: Some(raw), .. }
+                | Item::Entry { raw: Some(raw), .. }
+                | Item::Comment(raw) => out.push_str(raw),
+                Item::Section {
+                    subsection,
+                    raw: None,
+                    spelling,
+                    ..
+                } => {
+                    out.push('\n');
+                    out.push_str(&format_section_header(spelling, subsection.as_deref()));
+                }
+                Item::Entry {
+                    entry,
+                    raw: None,
+                    spelling,
+                } => {
+                    out.push_str("\n\t");
+                    out.push_str(spelling);
+                    if let Some(value) = &entry.value {
+                        out.push_str(" = ");
+                        out.push_str(&format_value(value));
+                    }
+                }
+            }
+        }
+        out.push_str(&self.tail);
+
+        let out = out.strip_prefix('\n').unwrap_or(&out);
+        if out.is_empty() || out.ends_with('\n') {
+            f.write_str(out)
+        } else {
+            writeln!(f, "{}", out)
+        }
+    }
+}
+
+// Renders `[section "subsection"]` with the subsection quoted as git expects.
+pub fn format_section_header(section: &str, subsection: Option<&str>) -> String {
+    match subsection {
+        Some(sub) => format!(
+            "[{} \"{}\"]",
+            section,
+            sub.replace('\\', "\\\\").replace('"', "\\\"")
+        ),
+        None => format!("[{}]", section),
+    }
+}
+
+// Renders a value so that git reads it back unchanged.
+pub fn format_value(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+    let needs_quotes = value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace)
+        || value.contains(['#', ';']);
+    if needs_quotes {
+        format!("\"{}\"", escaped)
+    } else {
+        escaped
+    }
+}
+
+// Whether an `includeIf "gitdir:<pattern>"` condition applies to `git_dir`.
+// Mirrors git: `~/` is the home directory, `./` is relative to the including
+// file, a trailing `/` implies `**`, and other relative patterns float.
+pub fn gitdir_matches(
+    pattern: &str,
+    git_dir: &Path,
+    base_dir: Option<&Path>,
+    case_insensitive: bool,
+) -> Result<bool> {
+    let mut expanded = match (pattern.strip_prefix("./"), base_dir) {
+        (Some(rest), Some(base)) => base.join(rest).to_string_lossy().to_string(),
+        _ => expand_path(pattern)?.to_string_lossy().to_string(),
+    };
+    if expanded.ends_with('/') {
+        expanded.push_str("**");
+    }
+    if !expanded.starts_with('/') {
+        expanded = format!("**/{}", expanded);
+    }
+
+    // Like git, try both the path as given and its symlink-resolved form
+    let mut candidates = vec![git_dir.to_string_lossy().to_string()];
+    if let Ok(real) = git_dir.canonicalize() {
+        candidates.push(real.to_string_lossy().to_string());
+    }
+
+    if case_insensitive {
+        let expanded = expanded.to_lowercase();
+        Ok(candidates
+            .iter()
+            .any(|c| glob_match(&expanded, &c.to_lowercase())))
+    } else {
+        Ok(candidates.iter().any(|c| glob_match(&expanded, c)))
+    }
+}
+
+// Reads `path` and every file it includes, returning entries in the order git
+// sees them, each paired with the file it was read from. `includeIf "gitdir:..."`
+// is evaluated against `git_dir` when given; conditions that need more context
+// (onbranch, hasconfig) are skipped.
+pub fn load_with_includes(path: &Path, git_dir: Option<&Path>) -> Result<Vec<(PathBuf, Entry)>> {
+    let mut entries = Vec::new();
+    collect_entries(path, git_dir, &mut Vec::new(), &mut entries)?;
+    Ok(entries)
+}
+
+// `stack` holds the files currently being read, outermost first.
+fn collect_entries(
+    path: &Path,
+    git_dir: Option<&Path>,
+    stack: &mut Vec<PathBuf>,
+    out: &mut Vec<(PathBuf, Entry)>,
+) -> Result<()> {
+    if stack.len() > MAX_INCLUDE_DEPTH {
+        bail!("Exceeded maximum include depth at {:?}", path);
+    }
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if stack.contains(&canonical) {
+        bail!("Include cycle detected: {:?} includes itself", path);
+    }
+
+    let config = GitConfig::load(path)?;
+    stack.push(canonical);
+    let base_dir = path.parent().map(Path::to_path_buf);
+    for entry in config.entries() {
+        out.push((path.to_path_buf(), entry.clone()));
+
+        let Some(target) = include_target(entry, git_dir, base_dir.as_deref())? else {
+            continue;
+        };
+        let target = resolve_include_path(&target, base_dir.as_deref())?;
+        // Missing include files are silently ignored, as git does
+        if target.exists() {
+            collect_entries(&target, git_dir, stack, out)?;
+        }
+    }
+    stack.pop();
+    Ok(())
+}
+
+fn include_target(
+    entry: &Entry,
+    git_dir: Option<&Path>,
+    base_dir: Option<&Path>,
+) -> Result<Option<String>> {
+    if entry.name != "path" {
+        return Ok(None);
+    }
+    let Some(value) = &entry.value else {
+        return Ok(None);
+    };
+
+    match (entry.section.as_str(), entry.subsection.as_deref()) {
+        ("include", None) => Ok(Some(value.clone())),
+        ("includeif", Some(condition)) => {
+            let Some(git_dir) = git_dir else {
+                return Ok(None);
+            };
+            let applies = if let Some(pattern) = condition.strip_prefix("gitdir:") {
+                gitdir_matches(pattern, git_dir, base_dir, false)?
+            } else if let Some(pattern) = condition.strip_prefix("gitdir/i:") {
+                gitdir_matches(pattern, git_dir, base_dir, true)?
+            } else {
+                false
+            };
+            Ok(applies.then(|| value.clone()))
+        }
+        _ => Ok(None),
+    }
+}
+
+pub fn resolve_include_path(target: &str, base_dir: Option<&Path>) -> Result<PathBuf> {
+    let path = expand_path(target)?;
+    match base_dir {
+        Some(base) if path.is_relative() => Ok(base.join(path)),
+        _ => Ok(path),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    // Start of the text not yet claimed by an item
+    mark: usize,
+    items: Vec<Item>,
+}
+
+impl Parser {
+    fn new(text: &str) -> Parser {
+        Parser {
+            chars: text.chars().collect(),
+            pos: 0,
+            line: 1,
+            mark: 0,
+            items: Vec::new(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        // CRLF is read as a plain newline, as git does
+        match self.chars.get(self.pos) {
+            Some('\r') if self.chars.get(self.pos + 1) == Some(&'\n') => Some('\n'),
+            other => other.copied(),
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        if self.chars[self.pos] == '\r' && c == '\n' {
+            self.pos += 1;
+        }
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn take_raw(&mut self) -> String {
+        let raw: String = self.chars[self.mark..self.pos].iter().collect();
+        self.mark = self.pos;
+        raw
+    }
+
+    fn error(&self, msg: &str) -> anyhow::Error {
+        anyhow!("{} at line {}", msg, self.line)
+    }
+
+    fn skip_to_eol(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '\n' {
+                break;
+            }
+            self.bump();
+        }
+    }
+
+    fn parse(mut self) -> Result<GitConfig> {
+        let mut current: Option<(String, Option<String>)> = None;
+
+        loop {
+            while self.peek().is_some_and(char::is_whitespace) {
+                self.bump();
+            }
+            let Some(c) = self.peek() else {
+                break;
+            };
+
+            if c == '#' || c == ';' {
+                self.skip_to_eol();
+                let raw = self.take_raw();
+                self.items.push(Item::Comment(raw));
+            } else if c == '[' {
+                self.bump();
+                let (section, subsection, spelling) = self.parse_section_header()?;
+                let raw = self.take_raw();
+                current = Some((section.clone(), subsection.clone()));
+                self.items.push(Item::Section {
+                    spelling,
+                    section,
+                    subsection,
+                    raw: Some(raw),
+                });
+            } else if c.is_ascii_alphabetic() {
+                let Some((section, subsection)) = current.clone() else {
+                    return Err(self.error("Key outside of any section"));
+                };
+                let (spelling, value) = self.parse_variable()?;
+                let raw = self.take_raw();
+                self.items.push(Item::Entry {
+                    entry: Entry {
+                        section,
+                        subsection,
+                        name: spelling.to_ascii_lowercase(),
+                        value,
+                    },
+                    spelling,
+                    raw: Some(raw),
+                });
+            } else {
+                return Err(self.error(&format!("Unexpected character '{}'", c)));
+            }
+        }
+
+        let tail = self.take_raw();
+        Ok(GitConfig {
+            items: self.items,
+            tail,
+        })
+    }
+
+    // Returns the lowercased section, its subsection and the original spelling.
+    fn parse_section_header(&mut self) -> Result<(String, Option<String>, String)> {
+        let mut spelling = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                spelling.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        if spelling.is_empty() {
+            return Err(self.error("Empty section name"));
+        }
+        let name = spelling.to_ascii_lowercase();
+
+        match self.bump() {
+            Some(']') => {
+                // Deprecated `[section.subsection]` syntax
+                match (name.split_once('.'), spelling.split_once('.')) {
+                    (Some((section, sub)), Some((spelled, _))) => Ok((
+                        section.to_string(),
+                        Some(sub.to_string()),
+                        spelled.to_string(),
+                    )),
+                    _ => Ok((name, None, spelling)),
+                }
+            }
+            Some(c) if c == ' ' || c == '\t' => {
+                while self.peek().is_some_and(|c| c == ' ' || c == '\t') {
+                    self.bump();
+                }
+                if self.bump() != Some('"') {
+                    return Err(self.error("Expected quoted subsection"));
+                }
+                let mut sub = String::new();
+                loop {
+                    match self.bump() {
+                        None | Some('\n') => return Err(self.error("Unterminated subsection")),
+                        Some('"') => break,
+                        Some('\\') => match self.bump() {
+                            None | Some('\n') => return Err(self.error("Unterminated subsection")),
+                            Some(c) => sub.push(c),
+                        },
+                        Some(c) => sub.push(c),
+                    }
+                }
+                if self.bump() != Some(']') {
+                    return Err(self.error("Expected ']' after subsection"));
+                }
+                Ok((name, Some(sub), spelling))
+            }
+            _ => Err(self.error("Malformed section header")),
+        }
+    }
+
+    // Returns the original spelling of the key name and its value.
+    fn parse_variable(&mut self) -> Result<(String, Option<String>)> {
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                name.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+
+        while self.peek().is_some_and(|c| c == ' ' || c == '\t') {
+            self.bump();
+        }
+
+        match self.peek() {
+            None | Some('\n') => Ok((name, None)),
+            Some('#') | Some(';') => {
+                self.skip_to_eol();
+                Ok((name, None))
+            }
+            Some('=') => {
+                self.bump();
+                let value = self.parse_value()?;
+                Ok((name, Some(value)))
+            }
+            Some(c) => Err(self.error(&format!("Invalid character '{}' in key '{}'", c, name))),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<String> {
+        let mut value = String::new();
+        let mut pending_spaces = String::new();
+        let mut in_quote = false;
+
+        loop {
+            match self.peek() {
+                None | Some('\n') => {
+                    if in_quote {
+                        return Err(self.error("Unterminated quoted value"));
+                    }
+                    break;
+                }
+                Some('#') | Some(';') if !in_quote => {
+                    self.skip_to_eol();
+                    break;
+                }
+                Some(c) if !in_quote && c.is_whitespace() => {
+                    // Leading and trailing whitespace is dropped, inner whitespace kept
+                    if !value.is_empty() {
+                        pending_spaces.push(c);
+                    }
+                    self.bump();
+                }
+                Some('"') => {
+                    in_quote = !in_quote;
+                    self.bump();
+                }
+                Some('\\') => {
+                    self.bump();
+                    let escaped = match self.bump() {
+                        Some('\n') => continue,
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some('n') => '\n',
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        _ => return Err(self.error("Invalid escape sequence in value")),
+                    };
+                    value.push_str(&pending_spaces);
+                    pending_spaces.clear();
+                    value.push(escaped);
+                }
+                Some(c) => {
+                    value.push_str(&pending_spaces);
+                    pending_spaces.clear();
+                    value.push(c);
+                    self.bump();
+                }
+            }
+        }
+        Ok(value)
+    }
+}
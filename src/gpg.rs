@@ -0,0 +1,169 @@
+use crate::config::NajConfig;
+use crate::git::{get_profile_path, profile_value};
+use crate::gitconfig::GitConfig;
+use crate::profile::ProfileId;
+use anyhow::{anyhow, bail, Context, Result};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// A secret key from the gpg keyring that can sign.
+struct SecretKey {
+    fingerprint: String,
+    uids: Vec<String>,
+}
+
+// The gpg binary a profile signs with.
+fn gpg_program(profile_path: &Path) -> Result<String> {
+    Ok(profile_value(profile_path, "gpg.program")?.unwrap_or_else(|| "gpg".to_string()))
+}
+
+// Lists the usable signing keys of the keyring, parsed from gpg's
+// machine-readable `--with-colons` output. Revoked, expired and disabled keys
+// are skipped.
+fn secret_keys(program: &str) -> Result<Vec<SecretKey>> {
+    let output = Command::new(program)
+        .args(["--batch", "--with-colons", "--list-secret-keys"])
+        .stderr(Stdio::null())
+        .output()
+        .with_context(|| format!("Failed to run '{}' (is GnuPG installed?)", program))?;
+    if !output.status.success() {
+        bail!("'{}' could not list the secret keys", program);
+    }
+
+    let mut keys: Vec<SecretKey> = Vec::new();
+    // Whether the records that follow belong to a usable primary key
+    let mut usable = false;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let fields: Vec<&str> = line.split(':').collect();
+        let field = |i: usize| fields.get(i).copied().unwrap_or("");
+        match field(0) {
+            "sec" => {
+                let validity = field(1);
+                let capabilities = field(11);
+                usable = !matches!(validity, "r" | "e" | "d") && capabilities.contains('S');
+                if usable {
+                    keys.push(SecretKey {
+                        fingerprint: String::new(),
+                        uids: Vec::new(),
+                    });
+                }
+            }
+            "ssb" => usable = false,
+            "fpr" if usable => {
+                if let Some(key) = keys.last_mut().filter(|k| k.fingerprint.is_empty()) {
+                    key.fingerprint = field(9).to_string();
+                }
+            }
+            "uid" if usable && !matches!(field(1), "r" | "e") => {
+                if let Some(key) = keys.last_mut() {
+                    key.uids.push(field(9).replace("\\x3a", ":"));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(keys)
+}
+
+fn uid_has_email(uid: &str, email: &str) -> bool {
+    let address = match (uid.rfind('<'), uid.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &uid[start + 1..end],
+        _ => uid,
+    };
+    address.trim().eq_ignore_ascii_case(email)
+}
+
+// Binds an OpenPGP key from the local keyring to a profile. Keys whose UIDs
+// carry the profile's email are offered; with several, the user picks one.
+pub fn link(config: &NajConfig, id: &ProfileId) -> Result<()> {
+    let profile_path = get_profile_path(config, id)?;
+    let email = profile_value(&profile_path, "user.email")?
+        .ok_or_else(|| anyhow!("Profile '{}' has no user.email to look up keys for", id))?;
+
+    let candidates: Vec<SecretKey> = secret_keys(&gpg_program(&profile_path)?)?
+        .into_iter()
+        .filter(|key| key.uids.iter().any(|uid| uid_has_email(uid, &email)))
+        .collect();
+
+    let key = match candidates.len() {
+        0 => bail!(
+            "No secret signing key in your gpg keyring has a UID with <{}>",
+            email
+        ),
+        1 => &candidates[0],
+        n => {
+            println!("Secret keys for <{}>:", email);
+            for (i, key) in candidates.iter().enumerate() {
+                println!("  {}) {}  {}", i + 1, key.fingerprint, key.uids.join(", "));
+            }
+            &candidates[pick(n)? - 1]
+        }
+    };
+
+    let mut profile = GitConfig::load(&profile_path)?;
+    profile.set("user.signingkey", &key.fingerprint)?;
+    profile.set("gpg.format", "openpgp")?;
+    profile.save(&profile_path)?;
+
+    println!(
+        "Profile '{}' now signs with OpenPGP key {}",
+        id, key.fingerprint
+    );
+    Ok(())
+}
+
+// Asks for a number between 1 and `count` until a valid one is given.
+fn pick(count: usize) -> Result<usize> {
+    loop {
+        print!("Pick a key [1-{}]: ", count);
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer)? == 0 {
+            bail!("No key picked");
+        }
+        match answer.trim().parse::<usize>() {
+            Ok(n) if (1..=count).contains(&n) => return Ok(n),
+            _ => println!("Please enter a number between 1 and {}", count),
+        }
+    }
+}
+
+// Warns when a profile signs with an OpenPGP key that the keyring lacks, as
+// every signed commit would then fail.
+pub fn warn_if_key_missing(id: &ProfileId, profile_path: &Path) -> Result<()> {
+    let Some(signing_key) = profile_value(profile_path, "user.signingkey")? else {
+        return Ok(());
+    };
+    let format = profile_value(profile_path, "gpg.format")?;
+    if format.is_some_and(|f| !f.eq_ignore_ascii_case("openpgp")) {
+        return Ok(());
+    }
+
+    let program = gpg_program(profile_path)?;
+    let found = Command::new(&program)
+        .args(["--batch", "--list-secret-keys", &signing_key])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    match found {
+        Ok(status) if status.success() => {}
+        Ok(_) => {
+            println!(
+                "\n⚠️  WARNING: Signing key {} is not in your gpg keyring.",
+                signing_key
+            );
+            println!(
+                "Signed commits will fail; import the key or run `naj gpg link {}`.",
+                id
+            );
+        }
+        Err(_) => {
+            println!(
+                "\n⚠️  WARNING: Cannot check signing key {}: '{}' is not installed.",
+                signing_key, program
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use crate::config::NajConfig;
+use crate::git::{
+    ensure_git_repo, get_profile_path, git_dir, git_in, list_remote_urls, read_profile_config,
+};
+use crate::profile::ProfileId;
+use crate::rules::{match_dir_rule, match_url_rule};
+use crate::status::bound_profiles;
+use crate::utils::shell_quote;
+use anyhow::{anyhow, bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const GUARD_MARKER: &str = "# naj-guard";
+const GUARD_HOOKS: &[&str] = &["pre-commit", "pre-push"];
+
+// Suffix given to a pre-existing hook so the guard can chain into it.
+const CHAINED_SUFFIX: &str = ".pre-naj";
+
+// Resolves the profile a repository is expected to use, and why.
+// A local include always wins; otherwise directory rules, then URL rules apply.
+pub fn expected_profile(config: &NajConfig, repo: &Path) -> Result<Option<(ProfileId, String)>> {
+    let (bound, _) = bound_profiles(config, repo)?;
+    if let Some(id) = bound.last() {
+        return Ok(Some((id.clone(), "local include".to_string())));
+    }
+    rule_profile(config, repo)
+}
+
+// The profile the directory and URL rules select for the current repository.
+pub fn rule_profile(config: &NajConfig, repo: &Path) -> Result<Option<(ProfileId, String)>> {
+    if let Some(rule) = match_dir_rule(config, &git_dir(repo)?)? {
+        return Ok(Some((
+            rule.profile.clone(),
+            format!("directory rule '{}'", rule.path),
+        )));
+    }
+
+    for (name, url) in list_remote_urls(repo)? {
+        if let Some(rule) = match_url_rule(config, &url) {
+            return Ok(Some((
+                rule.profile.clone(),
+                format!("URL rule '{}' (remote {})", rule.pattern, name),
+            )));
+        }
+    }
+
+    Ok(None)
+}
+
+// The author email git would record for a commit made right now.
+fn author_email(repo: &Path) -> Result<Option<String>> {
+    let output = git_in(repo)
+        .args(["var", "GIT_AUTHOR_IDENT"])
+        .output()
+        .context("Failed to query author identity")?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let ident = String::from_utf8_lossy(&output.stdout);
+    let email = ident
+        .split_once('<')
+        .and_then(|(_, rest)| rest.split_once('>'))
+        .map(|(email, _)| email.trim().to_string());
+    Ok(email.filter(|e| !e.is_empty()))
+}
+
+pub fn run_check(config: &NajConfig, repo: &Path) -> Result<()> {
+    ensure_git_repo(repo)?;
+
+    let Some((profile_id, reason)) = expected_profile(config, repo)? else {
+        // Repositories naj knows nothing about are not guarded
+        return Ok(());
+    };
+
+    let profile_path = get_profile_path(config, &profile_id)?;
+    let expected = read_profile_config(&profile_path)?
+        .into_iter()
+        .rev()
+        .find(|(k, _)| k.eq_ignore_ascii_case("user.email"))
+        .map(|(_, v)| v);
+    let actual = author_email(repo)?;
+
+    if expected.is_none() || expected != actual {
+        bail!(
+            "naj guard: author email '{}' does not match profile '{}' ('{}', selected by {}).\n\
+             Run `naj {}` to bind this repository, or `naj status` for details.",
+            actual.as_deref().unwrap_or(""),
+            profile_id,
+            expected.as_deref().unwrap_or(""),
+            reason,
+            profile_id
+        );
+    }
+    Ok(())
+}
+
+fn hooks_dir(repo: &Path) -> Result<PathBuf> {
+    // --git-path honours core.hooksPath and linked worktrees
+    let output = git_in(repo)
+        .args(["rev-parse", "--git-path", "hooks"])
+        .output()
+        .context("Failed to locate hooks directory")?;
+    if !output.status.success() {
+        return Err(anyhow!("Not a git repository"));
+    }
+    Ok(repo.join(String::from_utf8_lossy(&output.stdout).trim_end_matches('\n')))
+}
+
+fn is_guard_hook(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .map(|content| content.contains(GUARD_MARKER))
+        .unwrap_or(false)
+}
+
+fn hook_script(naj: &Path) -> String {
+    format!(
+        r#"#!/bin/sh
+{marker}: installed by `naj guard install`, remove with `naj guard uninstall`.
+{naj} guard check || exit 1
+if [ -x "$0{suffix}" ]; then
+    exec "$0{suffix}" "$@"
+fi
+"#,
+        marker = GUARD_MARKER,
+        naj = shell_quote(&naj.to_string_lossy()),
+        suffix = CHAINED_SUFFIX
+    )
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+pub fn install(repo: &Path) -> Result<()> {
+    ensure_git_repo(repo)?;
+    let hooks = hooks_dir(repo)?;
+    fs::create_dir_all(&hooks).context("Failed to create hooks directory")?;
+    let naj = std::env::current_exe().context("Failed to locate naj binary")?;
+
+    for name in GUARD_HOOKS {
+        let hook = hooks.join(name);
+        let chained = hooks.join(format!("{}{}", name, CHAINED_SUFFIX));
+
+        // Keep an existing foreign hook alive by chaining into it
+        if hook.exists() && !is_guard_hook(&hook) {
+            if chained.exists() {
+                bail!(
+                    "Cannot install {} guard: both {:?} and {:?} exist",
+                    name,
+                    hook,
+                    chained
+                );
+            }
+            fs::rename(&hook, &chained)
+                .with_context(|| format!("Failed to move existing hook {:?}", hook))?;
+            println!("Chained existing {} hook as {:?}", name, chained);
+        }
+
+        fs::write(&hook, hook_script(&naj))
+            .with_context(|| format!("Failed to write hook {:?}", hook))?;
+        make_executable(&hook)?;
+    }
+
+    println!("Installed naj guard hooks in {:?}", hooks);
+    Ok(())
+}
+
+pub fn uninstall(repo: &Path) -> Result<()> {
+    ensure_git_repo(repo)?;
+    let hooks = hooks_dir(repo)?;
+
+    for name in GUARD_HOOKS {
+        let hook = hooks.join(name);
+        let chained = hooks.join(format!("{}{}", name, CHAINED_SUFFIX));
+
+        if !is_guard_hook(&hook) {
+            continue;
+        }
+        fs::remove_file(&hook).with_context(|| format!("Failed to remove hook {:?}", hook))?;
+        if chained.exists() {
+            fs::rename(&chained, &hook)
+                .with_context(|| format!("Failed to restore hook {:?}", chained))?;
+        }
+    }
+
+    println!("Removed naj guard hooks from {:?}", hooks);
+    Ok(())
+}
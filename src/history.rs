@@ -0,0 +1,232 @@
+use crate::config::NajConfig;
+use crate::git::{
+    ensure_git_repo, get_profile_dir, is_mocking, is_profile_include, switch_config_path,
+};
+use crate::gitconfig::GitConfig;
+use crate::snapshot::ConfigSnapshot;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Older states are dropped once the journal grows beyond this.
+const MAX_ENTRIES: usize = 20;
+
+#[derive(Serialize, Deserialize, Default)]
+struct Journal {
+    #[serde(default)]
+    entries: Vec<JournalEntry>,
+    // Entry numbers are never reused, even after `naj undo` dropped entries
+    #[serde(default)]
+    next_seq: u64,
+}
+
+// One saved `.git/config`, taken right before `action` changed it.
+#[derive(Serialize, Deserialize)]
+struct JournalEntry {
+    seq: u64,
+    time: u64,
+    action: String,
+    // Profiles the saved state was bound to through include.path
+    #[serde(default)]
+    profiles: Vec<String>,
+    // The file did not exist yet (e.g. config.worktree before the first
+    // per-worktree switch); restoring this entry deletes it
+    #[serde(default)]
+    absent: bool,
+}
+
+// The journal lives next to the config file switches write to, so linked
+// worktrees share it unless profiles are bound per worktree.
+fn history_dir(config_path: &Path) -> Result<PathBuf> {
+    let git_dir = config_path
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid local config path {:?}", config_path))?;
+    Ok(git_dir.join("naj").join("history"))
+}
+
+fn state_path(dir: &Path, seq: u64) -> PathBuf {
+    dir.join(format!("{}.config", seq))
+}
+
+fn load_journal(dir: &Path) -> Result<Journal> {
+    let path = dir.join("journal.toml");
+    if !path.exists() {
+        return Ok(Journal::default());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))
+}
+
+fn save_journal(dir: &Path, journal: &Journal) -> Result<()> {
+    let path = dir.join("journal.toml");
+    fs::write(&path, toml::to_string(journal)?)
+        .with_context(|| format!("Failed to write {:?}", path))
+}
+
+fn bound_profile_ids(content: &[u8], profile_dir: &Path) -> Vec<String> {
+    let Ok(local) = GitConfig::parse(&String::from_utf8_lossy(content)) else {
+        return Vec::new();
+    };
+    local
+        .get_all("include.path")
+        .into_iter()
+        .filter(|val| is_profile_include(val, profile_dir))
+        .filter_map(|val| Path::new(val).file_stem())
+        .map(|stem| stem.to_string_lossy().to_string())
+        .collect()
+}
+
+// Saves the state held by `snapshot` unless the file still has that content.
+pub fn record(config: &NajConfig, snapshot: &ConfigSnapshot, action: &str) -> Result<()> {
+    let content = snapshot.content();
+    if fs::read(snapshot.path()).ok().as_deref() == content {
+        return Ok(());
+    }
+
+    let dir = history_dir(snapshot.path())?;
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    let mut journal = load_journal(&dir)?;
+
+    let seq = journal
+        .entries
+        .last()
+        .map(|e| e.seq + 1)
+        .unwrap_or(1)
+        .max(journal.next_seq);
+    journal.next_seq = seq + 1;
+    if let Some(content) = content {
+        fs::write(state_path(&dir, seq), content)
+            .with_context(|| format!("Failed to save config state #{}", seq))?;
+    }
+    journal.entries.push(JournalEntry {
+        seq,
+        time: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        action: action.to_string(),
+        profiles: match content {
+            Some(content) => bound_profile_ids(content, &get_profile_dir(config)?),
+            None => Vec::new(),
+        },
+        absent: content.is_none(),
+    });
+
+    while journal.entries.len() > MAX_ENTRIES {
+        let old = journal.entries.remove(0);
+        let _ = fs::remove_file(state_path(&dir, old.seq));
+    }
+    save_journal(&dir, &journal)
+}
+
+// Formats a Unix timestamp as a UTC date and time.
+fn format_time(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil-from-days conversion (proleptic Gregorian calendar)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+fn describe_profiles(profiles: &[String]) -> String {
+    if profiles.is_empty() {
+        "(none)".to_string()
+    } else {
+        profiles.join(", ")
+    }
+}
+
+pub fn run_history(repo: &Path) -> Result<()> {
+    ensure_git_repo(repo)?;
+    let journal = load_journal(&history_dir(&switch_config_path(repo)?)?)?;
+
+    if journal.entries.is_empty() {
+        println!("No switch history for this repository.");
+        return Ok(());
+    }
+
+    for entry in journal.entries.iter().rev() {
+        println!(
+            "#{:<4} {}  profile: {:<12} before {}",
+            entry.seq,
+            format_time(entry.time),
+            describe_profiles(&entry.profiles),
+            entry.action
+        );
+    }
+    Ok(())
+}
+
+// Restores the state saved as `target` (the most recent one by default).
+// That entry and everything newer is dropped from the journal, and the state
+// being replaced is journaled in turn so the undo can itself be undone.
+pub fn run_undo(config: &NajConfig, repo: &Path, target: Option<u64>) -> Result<()> {
+    ensure_git_repo(repo)?;
+    let config_path = switch_config_path(repo)?;
+    let dir = history_dir(&config_path)?;
+    let mut journal = load_journal(&dir)?;
+
+    let index = match target {
+        Some(seq) => journal
+            .entries
+            .iter()
+            .position(|e| e.seq == seq)
+            .ok_or_else(|| anyhow!("No history entry #{} (see `naj history`)", seq))?,
+        None => journal
+            .entries
+            .len()
+            .checked_sub(1)
+            .ok_or_else(|| anyhow!("Nothing to undo: no switch history for this repository"))?,
+    };
+    let entry = &journal.entries[index];
+
+    let saved = state_path(&dir, entry.seq);
+    if is_mocking() {
+        eprintln!("[DRY-RUN] restore {:?} from {:?}", config_path, saved);
+        return Ok(());
+    }
+
+    let restored = if entry.absent {
+        ConfigSnapshot::absent(&config_path)
+    } else {
+        let content = fs::read(&saved)
+            .with_context(|| format!("Failed to read saved config state #{}", entry.seq))?;
+        ConfigSnapshot::with_content(&config_path, content)
+    };
+    let current = ConfigSnapshot::take(&config_path)?;
+    restored.restore()?;
+    println!(
+        "Restored local config #{} (before {}), profile: {}",
+        entry.seq,
+        entry.action,
+        describe_profiles(&entry.profiles)
+    );
+
+    let action = format!("undo to #{}", entry.seq);
+    for dropped in journal.entries.drain(index..) {
+        let _ = fs::remove_file(state_path(&dir, dropped.seq));
+    }
+    save_journal(&dir, &journal)?;
+    record(config, &current, &action)
+}
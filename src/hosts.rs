@@ -0,0 +1,241 @@
+use crate::config::{HostAlias, NajConfig};
+use crate::git::{get_profile_path, is_mocking, profile_file};
+use crate::gitconfig::GitConfig;
+use crate::keys::profile_identity_file;
+use crate::manage::profile_ids;
+use crate::utils::expand_path;
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::Path;
+
+const BLOCK_BEGIN: &str =
+    "# BEGIN naj managed block. Do not edit: run `naj hosts apply` to regenerate.";
+const BLOCK_END: &str = "# END naj managed block";
+
+// Splits an SSH config into the text before naj's block, the block itself and
+// the text after it. Without a block, everything is "before".
+fn split_block(content: &str) -> (&str, Option<&str>, &str) {
+    let Some(start) = content.find(BLOCK_BEGIN) else {
+        return (content, None, "");
+    };
+    let Some(end) = content[start..].find(BLOCK_END).map(|i| start + i) else {
+        return (content, None, "");
+    };
+    let after = &content[end + BLOCK_END.len()..];
+    let after = after.strip_prefix('\n').unwrap_or(after);
+    (&content[..start], Some(&content[start..end]), after)
+}
+
+// The aliases declared by an existing managed block.
+fn block_aliases(block: &str) -> Vec<String> {
+    block
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Host "))
+        .map(|alias| alias.trim().to_string())
+        .collect()
+}
+
+fn read_ssh_config(path: &Path) -> Result<String> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {:?}", path)),
+    }
+}
+
+// Writes `block` in place of naj's previous block. A new block goes to the top
+// of the file, because ssh uses the first value it finds for each option.
+fn write_ssh_config(path: &Path, content: &str, block: Option<&str>) -> Result<()> {
+    let (before, _, after) = split_block(content);
+    let new_content = match (block, content.contains(BLOCK_BEGIN)) {
+        (Some(block), true) => format!("{}{}{}", before, block, after),
+        (Some(block), false) if content.is_empty() => block.to_string(),
+        (Some(block), false) => format!("{}\n{}", block, content),
+        (None, _) => {
+            let after = if before.is_empty() {
+                after.strip_prefix('\n').unwrap_or(after)
+            } else {
+                after
+            };
+            format!("{}{}", before, after)
+        }
+    };
+    if new_content == content {
+        return Ok(());
+    }
+
+    if is_mocking() {
+        eprintln!("[DRY-RUN] write {:?}:\n{}", path, new_content);
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    }
+    fs::write(path, new_content).with_context(|| format!("Failed to write {:?}", path))
+}
+
+fn url_subsection(user: &str, alias: &str) -> String {
+    format!("{}@{}:", user, alias)
+}
+
+// Removes the rewrites to the given aliases from every profile, then adds
+// each alias's rewrites to its own profile. Profiles are only written when
+// they change.
+fn update_profile_rewrites(
+    config: &NajConfig,
+    stale: &[String],
+    aliases: &[HostAlias],
+) -> Result<()> {
+    for id in profile_ids(config)? {
+        let path = profile_file(config, &id)?;
+        let before = fs::read_to_string(&path)?;
+        let mut profile = GitConfig::parse(&before)
+            .with_context(|| format!("Failed to parse config file {:?}", path))?;
+
+        for alias in stale {
+            for subsection in profile_url_subsections(&profile, alias) {
+                profile.remove_section("url", Some(&subsection));
+            }
+        }
+        for host in aliases.iter().filter(|h| h.profile == id) {
+            let subsection = url_subsection(&host.user, &host.alias());
+            let key = format!("url.{}.insteadOf", subsection);
+            profile.add(&key, &format!("{}@{}:", host.user, host.host))?;
+            profile.add(&key, &format!("ssh://{}@{}/", host.user, host.host))?;
+        }
+
+        if profile.to_string() != before {
+            if is_mocking() {
+                eprintln!("[DRY-RUN] write {:?}:\n{}", path, profile);
+            } else {
+                profile.save(&path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// The `url "<user>@<alias>:"` subsections of a profile for one alias.
+fn profile_url_subsections(profile: &GitConfig, alias: &str) -> Vec<String> {
+    let suffix = format!("@{}:", alias);
+    let mut subsections: Vec<String> = profile
+        .entries()
+        .filter(|e| e.section == "url")
+        .filter_map(|e| e.subsection.clone())
+        .filter(|sub| sub.ends_with(&suffix))
+        .collect();
+    subsections.dedup();
+    subsections
+}
+
+// Every alias naj manages: the configured ones, plus those still declared by
+// the SSH config block (e.g. after an alias was dropped from config.toml).
+pub fn managed_aliases(config: &NajConfig) -> Result<Vec<String>> {
+    let mut aliases: Vec<String> = config.host_aliases.iter().map(HostAlias::alias).collect();
+    let content = read_ssh_config(&expand_path(&config.ssh_config)?)?;
+    if let (_, Some(block), _) = split_block(&content) {
+        for alias in block_aliases(block) {
+            if !aliases.contains(&alias) {
+                aliases.push(alias);
+            }
+        }
+    }
+    Ok(aliases)
+}
+
+// The URL rewrites to naj's aliases in a repository's config, as
+// `url.<user>@<alias>:` section names. An override switch copies them from the
+// profile; left behind, they would push with that profile's key.
+pub fn alias_rewrite_sections(local: &GitConfig, aliases: &[String]) -> Vec<String> {
+    let mut sections = Vec::new();
+    for alias in aliases {
+        for subsection in profile_url_subsections(local, alias) {
+            let section = format!("url.{}", subsection);
+            if !sections.contains(&section) {
+                sections.push(section);
+            }
+        }
+    }
+    sections
+}
+
+// Regenerates naj's block in the SSH config from [[host_aliases]] and points
+// each profile's URL rewrites at its aliases. Running it twice changes nothing.
+pub fn apply_hosts(config: &NajConfig) -> Result<()> {
+    let mut entries = Vec::new();
+    let mut seen: Vec<String> = Vec::new();
+    for host in &config.host_aliases {
+        let alias = host.alias();
+        if seen.contains(&alias) {
+            bail!("Host alias '{}' is declared twice", alias);
+        }
+        let profile_path = get_profile_path(config, &host.profile)
+            .with_context(|| format!("Invalid host alias '{}'", alias))?;
+        let Some(identity) = profile_identity_file(&profile_path)? else {
+            bail!(
+                "Profile '{}' has no SSH key for host alias '{}'; run `naj key gen {}`",
+                host.profile,
+                alias,
+                host.profile
+            );
+        };
+
+        entries.push(format!(
+            "Host {}\n    HostName {}\n    User {}\n    IdentityFile {}\n    IdentitiesOnly yes\n",
+            alias,
+            host.host,
+            host.user,
+            identity.display()
+        ));
+        seen.push(alias);
+    }
+    let block = format!("{}\n{}{}\n", BLOCK_BEGIN, entries.join("\n"), BLOCK_END);
+
+    let ssh_config = expand_path(&config.ssh_config)?;
+    let content = read_ssh_config(&ssh_config)?;
+    let (_, old_block, _) = split_block(&content);
+    let mut stale = old_block.map(block_aliases).unwrap_or_default();
+    stale.extend(seen.iter().cloned());
+
+    let block = (!config.host_aliases.is_empty()).then_some(block);
+    write_ssh_config(&ssh_config, &content, block.as_deref())?;
+    update_profile_rewrites(config, &stale, &config.host_aliases)?;
+
+    println!(
+        "Applied {} host alias(es) via {:?}",
+        config.host_aliases.len(),
+        ssh_config
+    );
+    Ok(())
+}
+
+// Removes naj's block from the SSH config and the matching URL rewrites from
+// the profiles.
+pub fn remove_hosts(config: &NajConfig) -> Result<()> {
+    let ssh_config = expand_path(&config.ssh_config)?;
+    let content = read_ssh_config(&ssh_config)?;
+    let (_, old_block, _) = split_block(&content);
+    let mut stale = old_block.map(block_aliases).unwrap_or_default();
+    stale.extend(config.host_aliases.iter().map(HostAlias::alias));
+
+    write_ssh_config(&ssh_config, &content, None)?;
+    update_profile_rewrites(config, &stale, &[])?;
+
+    println!("Removed naj's host aliases from {:?}", ssh_config);
+    println!(
+        "Repositories switched with an override strategy keep copies of the rewrites \
+         until their next switch; `naj audit` lists them."
+    );
+    Ok(())
+}
+
+pub fn list_hosts(config: &NajConfig) -> Result<()> {
+    if config.host_aliases.is_empty() {
+        println!("No host aliases configured");
+        return Ok(());
+    }
+    for host in &config.host_aliases {
+        println!("{} ({}) -> {}", host.alias(), host.host, host.profile);
+    }
+    Ok(())
+}
@@ -0,0 +1,287 @@
+use crate::config::NajConfig;
+use crate::git::{get_profile_path, is_mocking, profile_value, read_profile_config};
+use crate::gitconfig::GitConfig;
+use crate::profile::ProfileId;
+use crate::utils::{expand_path, shell_quote};
+use anyhow::{anyhow, bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Where `naj key gen` puts the key of a profile.
+fn default_key_path(config: &NajConfig, id: &ProfileId) -> Result<PathBuf> {
+    Ok(expand_path(&config.key_dir)?.join(format!("naj_{}_ed25519", id)))
+}
+
+fn public_key_path(key: &Path) -> PathBuf {
+    let mut name = key.as_os_str().to_owned();
+    name.push(".pub");
+    PathBuf::from(name)
+}
+
+// The value a profile's core.sshCommand gets for a generated key.
+fn ssh_command_for(key: &Path) -> String {
+    format!(
+        "ssh -i {} -o IdentitiesOnly=yes",
+        shell_quote(&key.to_string_lossy())
+    )
+}
+
+// Splits a command line into words, honouring single and double quotes.
+fn split_words(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => word.extend(chars.next()),
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, '\\') => {
+                word.extend(chars.next());
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+// The identity file a profile's core.sshCommand points at, if any.
+pub fn profile_identity_file(profile_path: &Path) -> Result<Option<PathBuf>> {
+    let Some((_, command)) = read_profile_config(profile_path)?
+        .into_iter()
+        .rev()
+        .find(|(k, _)| k.eq_ignore_ascii_case("core.sshCommand"))
+    else {
+        return Ok(None);
+    };
+
+    let words = split_words(&command);
+    let identity = words.iter().enumerate().find_map(|(i, w)| {
+        if w == "-i" {
+            words.get(i + 1).cloned()
+        } else {
+            w.strip_prefix("-i")
+                .map(String::from)
+                .filter(|s| !s.is_empty())
+        }
+    });
+    identity.map(|p| expand_path(&p)).transpose()
+}
+
+// Generates an ed25519 key for a profile and points its core.sshCommand at it.
+// Without `no_passphrase`, ssh-keygen asks for a passphrase interactively.
+pub fn generate(config: &NajConfig, id: &ProfileId, no_passphrase: bool) -> Result<()> {
+    let profile_path = get_profile_path(config, id)?;
+    let key = default_key_path(config, id)?;
+    if key.exists() {
+        bail!(
+            "Key {:?} already exists; see it with `naj key show {}` or remove it first",
+            key,
+            id
+        );
+    }
+
+    let comment =
+        profile_value(&profile_path, "user.email")?.unwrap_or_else(|| format!("naj-{}", id));
+    let mut cmd = Command::new("ssh-keygen");
+    cmd.args(["-t", "ed25519", "-C", &comment, "-f"]).arg(&key);
+    if no_passphrase {
+        cmd.args(["-N", ""]);
+    }
+
+    if is_mocking() {
+        eprintln!("[DRY-RUN] {:?}", cmd);
+        return Ok(());
+    }
+
+    // Only a directory naj creates is locked down; one the user chose is left alone
+    let key_dir = key.parent().unwrap_or(Path::new("."));
+    if key_dir.is_dir() {
+        warn_if_open(key_dir)?;
+    } else {
+        fs::create_dir_all(key_dir).with_context(|| format!("Failed to create {:?}", key_dir))?;
+        restrict_permissions(key_dir)?;
+    }
+
+    let status = cmd
+        .status()
+        .context("Failed to run ssh-keygen (is OpenSSH installed?)")?;
+    if !status.success() {
+        bail!("ssh-keygen exited with status: {}", status);
+    }
+
+    let mut profile = GitConfig::load(&profile_path)?;
+    if let Some(previous) = profile.get("core.sshCommand") {
+        println!("Replacing core.sshCommand '{}'", previous);
+    }
+    profile.set("core.sshCommand", &ssh_command_for(&key))?;
+    profile.save(&profile_path)?;
+
+    println!("Generated {:?} for profile '{}'", key, id);
+    println!("Add this public key to your forge account:\n");
+    print!("{}", fs::read_to_string(public_key_path(&key))?);
+    Ok(())
+}
+
+// Prints the public key of the identity a profile uses.
+pub fn show(config: &NajConfig, id: &ProfileId) -> Result<()> {
+    let profile_path = get_profile_path(config, id)?;
+    let key = match profile_identity_file(&profile_path)? {
+        Some(key) => key,
+        None => default_key_path(config, id)?,
+    };
+    let public = public_key_path(&key);
+    let content = fs::read_to_string(&public).map_err(|_| {
+        anyhow!(
+            "No public key at {:?}; create one with `naj key gen {}`",
+            public,
+            id
+        )
+    })?;
+    print!("{}", content);
+    Ok(())
+}
+
+// The allowed_signers file naj maintains for a profile, next to the profile.
+fn allowed_signers_path(profile_path: &Path) -> PathBuf {
+    profile_path.with_extension("allowed_signers")
+}
+
+// Configures a profile to sign commits and tags with an SSH key, and trusts
+// that key for the profile's email in the profile's allowed_signers file, so
+// `git log --show-signature` can verify the signatures locally.
+// Without `key`, the key from the profile's core.sshCommand (or the one
+// `naj key gen` would create) is used.
+pub fn setup_signing(config: &NajConfig, id: &ProfileId, key: Option<&Path>) -> Result<()> {
+    let profile_path = get_profile_path(config, id)?;
+    let key = match key {
+        Some(key) => key.to_path_buf(),
+        None => match profile_identity_file(&profile_path)? {
+            Some(key) => key,
+            None => default_key_path(config, id)?,
+        },
+    };
+    // Both the private key and the .pub file may be given
+    let public = if key.extension().is_some_and(|ext| ext == "pub") {
+        key
+    } else {
+        public_key_path(&key)
+    };
+    let public = public.canonicalize().map_err(|_| {
+        anyhow!(
+            "No public key at {:?}; create one with `naj key gen {}`",
+            public,
+            id
+        )
+    })?;
+
+    let content = fs::read_to_string(&public)?;
+    let mut fields = content.split_whitespace();
+    let (Some(key_type), Some(key_data)) = (fields.next(), fields.next()) else {
+        bail!("{:?} does not contain an SSH public key", public);
+    };
+    let email = profile_value(&profile_path, "user.email")?
+        .ok_or_else(|| anyhow!("Profile '{}' has no user.email to trust the key for", id))?;
+
+    let signers_path = allowed_signers_path(&profile_path);
+    update_allowed_signers(&signers_path, &email, key_type, key_data)?;
+
+    let mut profile = GitConfig::load(&profile_path)?;
+    profile.set("gpg.format", "ssh")?;
+    profile.set("user.signingkey", &public.to_string_lossy())?;
+    profile.set(
+        "gpg.ssh.allowedSignersFile",
+        &signers_path.to_string_lossy(),
+    )?;
+    profile.set("commit.gpgsign", "true")?;
+    profile.set("tag.gpgsign", "true")?;
+    profile.save(&profile_path)?;
+
+    println!(
+        "Profile '{}' now signs commits and tags with {:?}",
+        id, public
+    );
+    println!("Trusted for {} in {:?}", email, signers_path);
+    Ok(())
+}
+
+// Adds `email key_type key_data` to an allowed_signers file. A previous line
+// for the same key is replaced; lines for other keys are kept, so signatures
+// made with an older key still verify.
+fn update_allowed_signers(path: &Path, email: &str, key_type: &str, key_data: &str) -> Result<()> {
+    let existing = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", path)),
+    };
+
+    let mut lines: Vec<String> = existing
+        .lines()
+        .filter(|line| !line.split_whitespace().any(|field| field == key_data))
+        .map(String::from)
+        .collect();
+    lines.push(format!(
+        "{} namespaces=\"git\" {} {}",
+        email, key_type, key_data
+    ));
+
+    fs::write(path, lines.join("\n") + "\n").with_context(|| format!("Failed to write {:?}", path))
+}
+
+#[cfg(unix)]
+fn restrict_permissions(dir: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(dir, fs::Permissions::from_mode(0o700))
+        .with_context(|| format!("Failed to restrict permissions of {:?}", dir))
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_dir: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn warn_if_open(dir: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = fs::metadata(dir)
+        .with_context(|| format!("Failed to read permissions of {:?}", dir))?
+        .permissions()
+        .mode();
+    if mode & 0o077 != 0 {
+        println!(
+            "\n⚠️  WARNING: Key directory {:?} is accessible by other users (mode {:o}).",
+            dir,
+            mode & 0o777
+        );
+        println!(
+            "Consider restricting it with `chmod 700 {}`.",
+            dir.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn warn_if_open(_dir: &Path) -> Result<()> {
+    Ok(())
+}
@@ -1,12 +1,29 @@
 use anyhow::Result;
-use clap::{CommandFactory, Parser};
+use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::{generate, Shell};
+use config::SwitchStrategy;
+use plan::PlanFormat;
+use profile::ProfileId;
+use std::ffi::OsString;
 use std::io;
+use std::path::PathBuf;
 
+mod audit;
 mod config;
 mod git;
+mod gitconfig;
+mod gpg;
+mod guard;
+mod history;
+mod hosts;
+mod keys;
 mod manage;
+mod plan;
+mod profile;
+mod rules;
 mod sanitizer;
+mod snapshot;
+mod status;
 mod utils;
 
 // --- 1. 定义 CLI 结构体 (带详细文档) ---
@@ -16,16 +33,20 @@ mod utils;
 #[command(version)] // 自动从 Cargo.toml 读取版本
 #[command(author = "Ringo")]
 #[command(about = "A secure, idempotent Git identity switcher.")]
+#[command(args_conflicts_with_subcommands = true)]
 #[command(
     long_about = "Naj (/*ŋˤajʔ/ 'I/Me') helps you manage multiple Git identities (Work, Personal, Open Source) without messing up your local config or SSH keys.\n\nIt ensures that the correct email, signing key, and SSH command are used for every commit."
 )]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// The Profile ID to switch to (e.g., 'work', 'personal').
     ///
     /// If arguments are provided after this ID, they are passed to git.
     /// Example: `naj work commit -m "fix"`
     #[arg(value_name = "PROFILE_ID")]
-    profile_id: Option<String>,
+    profile_id: Option<ProfileId>,
 
     /// Git arguments to execute immediately after switching.
     ///
@@ -43,13 +64,27 @@ struct Cli {
     #[arg(short, long, num_args = 3, value_names = ["NAME", "EMAIL", "ID"])]
     create: Option<Vec<String>>,
 
+    /// Edit a profile in $VISUAL or $EDITOR.
+    ///
+    /// The profile is validated when the editor exits; on errors you are
+    /// offered to re-open it.
+    #[arg(short, long, value_name = "ID")]
+    edit: Option<ProfileId>,
+
+    /// Make the profile created with --create extend an existing one.
+    ///
+    /// The new profile inherits every key of PARENT and overrides the ones it sets.
+    /// Example: `naj -c "Me" me@oss.org work-oss --extends work`
+    #[arg(long, value_name = "PARENT", requires = "create")]
+    extends: Option<ProfileId>,
+
     /// List all available profiles.
     #[arg(short, long)]
     list: bool,
 
     /// Remove a profile by ID.
     #[arg(short, long, value_name = "ID")]
-    remove: Option<String>,
+    remove: Option<ProfileId>,
 
     /// Force switch strategy (Perform Hard Clean).
     ///
@@ -58,6 +93,79 @@ struct Cli {
     #[arg(short, long)]
     force: bool,
 
+    /// Override the configured strategy for this invocation.
+    ///
+    /// Applies to switching and to `clone`/`init` setup.
+    /// One of: include, INCLUDE, override, OVERRIDE.
+    #[arg(short, long, value_name = "STRATEGY")]
+    strategy: Option<SwitchStrategy>,
+
+    /// Switch every repository under DIR to the profile.
+    ///
+    /// Each repository is switched on its own; failures are listed at the end
+    /// instead of stopping the run. Example: `naj work --recursive ~/work`
+    #[arg(
+        short = 'R',
+        long,
+        value_name = "DIR",
+        requires = "profile_id",
+        conflicts_with = "git_args"
+    )]
+    recursive: Option<PathBuf>,
+
+    /// Bind the profile to the current worktree only.
+    ///
+    /// Turns on `extensions.worktreeConfig`, after which every switch in this
+    /// repository writes to the worktree's own config.worktree instead of the
+    /// config all linked worktrees share.
+    #[arg(
+        long,
+        requires = "profile_id",
+        conflicts_with_all = ["git_args", "recursive", "dry_run"]
+    )]
+    worktree: bool,
+
+    /// Also switch every initialised submodule, recursively.
+    #[arg(
+        long,
+        requires = "profile_id",
+        conflicts_with_all = ["git_args", "recursive"]
+    )]
+    submodules: bool,
+
+    /// Show what a switch would change in .git/config without changing it.
+    ///
+    /// Lists the sections to remove, keys to unset, includes to add or remove,
+    /// and keys to inject. Example: `naj work -f --dry-run`
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Output format of the --dry-run plan.
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        default_value_t,
+        requires = "dry_run"
+    )]
+    format: PlanFormat,
+
+    /// Run as if naj was started in PATH instead of the current directory.
+    ///
+    /// Works like `git -C`: the repository is looked up from PATH, and
+    /// `clone`/`init` create the new repository relative to it.
+    #[arg(short = 'C', value_name = "PATH", global = true)]
+    directory: Option<PathBuf>,
+
+    /// Path to the repository's git dir, like `git --git-dir`.
+    ///
+    /// Passed as `--git-dir` to the git commands naj runs for the target
+    /// repository only; repositories visited by `--recursive`, `--submodules`
+    /// and `audit` keep their own. GIT_DIR and GIT_WORK_TREE set in the
+    /// environment are respected as well.
+    #[arg(long, value_name = "PATH", global = true)]
+    git_dir: Option<PathBuf>,
+
     /// Generate shell completion script.
     ///
     /// Usage: source <(naj --completion zsh)
@@ -65,10 +173,251 @@ struct Cli {
     completion: Option<Shell>,
 }
 
+#[derive(Subcommand)]
+enum Commands {
+    /// Pick the profile from the URL rules and switch to it.
+    ///
+    /// The current repository's remotes are matched against [[url_rules]].
+    /// With `clone <url>`, the URL being cloned decides instead.
+    /// Example: `naj auto clone git@github.com:acme/app.git`
+    Auto {
+        /// Force switch strategy (Perform Hard Clean).
+        #[arg(short, long)]
+        force: bool,
+
+        /// Override the configured strategy for this invocation.
+        #[arg(short, long, value_name = "STRATEGY")]
+        strategy: Option<SwitchStrategy>,
+
+        /// Show what the switch would change without changing it.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Output format of the --dry-run plan.
+        #[arg(
+            long,
+            value_enum,
+            value_name = "FORMAT",
+            default_value_t,
+            requires = "dry_run"
+        )]
+        format: PlanFormat,
+
+        /// Git arguments, handled as in `naj <PROFILE_ID> [GIT_ARGS]...`.
+        #[arg(
+            value_name = "GIT_ARGS",
+            trailing_var_arg = true,
+            allow_hyphen_values = true
+        )]
+        git_args: Vec<String>,
+    },
+    /// Report the identity of every repository under a directory.
+    ///
+    /// For each checkout, worktree and bare repository: the profile it uses,
+    /// the effective email, whether commits are signed, dirty local config,
+    /// and whether the directory and URL rules select another profile.
+    Audit {
+        /// Directory to scan.
+        #[arg(value_name = "DIR", default_value = ".")]
+        dir: PathBuf,
+
+        /// Switch repositories with findings to the profile their rules select.
+        #[arg(long)]
+        fix: bool,
+
+        /// With --fix, use the Hard variant of the switch strategy.
+        #[arg(short, long, requires = "fix")]
+        force: bool,
+    },
+    /// Show which profile the current repository is bound to.
+    ///
+    /// Reports the effective user.name, user.email, user.signingkey and
+    /// core.sshCommand, the scope each one comes from, and any mismatch
+    /// between the included profile and locally overridden keys.
+    #[command(visible_alias = "whoami")]
+    Status,
+    /// Print every key a profile resolves to, including inherited ones.
+    Show {
+        /// The profile to show.
+        #[arg(value_name = "PROFILE_ID")]
+        id: ProfileId,
+    },
+    /// Manage the SSH key of a profile.
+    Key {
+        #[command(subcommand)]
+        action: KeyAction,
+    },
+    /// Manage the OpenPGP signing key of a profile.
+    Gpg {
+        #[command(subcommand)]
+        action: GpgAction,
+    },
+    /// Manage SSH host aliases that give profiles their own `Host` entries.
+    Hosts {
+        #[command(subcommand)]
+        action: HostsAction,
+    },
+    /// List the saved states of this repository's local config.
+    ///
+    /// A state is saved every time naj switches the repository's profile.
+    History,
+    /// Restore the local config as it was before the last switch.
+    ///
+    /// With an entry number from `naj history`, go back to that state instead.
+    /// The restored entry and all newer ones are removed from the history, and
+    /// the state being replaced is saved as a new entry, so an undo can be
+    /// undone as well.
+    Undo {
+        /// History entry to restore (defaults to the most recent one).
+        #[arg(value_name = "ENTRY")]
+        entry: Option<u64>,
+    },
+    /// Guard commits and pushes against the wrong identity.
+    Guard {
+        #[command(subcommand)]
+        action: GuardAction,
+    },
+    /// Inspect naj's own configuration.
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Manage directory rules that bind repositories to profiles automatically.
+    Rules {
+        #[command(subcommand)]
+        action: RulesAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum RulesAction {
+    /// Regenerate the includeIf blocks for all [[rules]] in config.toml.
+    ///
+    /// The blocks are written to a naj-managed file which is included from
+    /// the global gitconfig, so matching repositories need no `naj <id>` call.
+    Apply,
+    /// List the configured directory rules.
+    List,
+}
+
+#[derive(Subcommand)]
+enum HostsAction {
+    /// Regenerate naj's block in the SSH config from [[host_aliases]].
+    ///
+    /// Each profile also gets `url.<alias>.insteadOf` rewrites, so clones and
+    /// fetches of the host go through its alias. Safe to run repeatedly.
+    Apply,
+    /// Remove naj's block from the SSH config and the rewrites from profiles.
+    Remove,
+    /// List the configured host aliases.
+    List,
+}
+
+#[derive(Subcommand)]
+enum KeyAction {
+    /// Generate an ed25519 key for a profile with ssh-keygen.
+    ///
+    /// The profile's core.sshCommand is set to use exactly this key.
+    Gen {
+        /// The profile to generate the key for.
+        #[arg(value_name = "PROFILE_ID")]
+        id: ProfileId,
+
+        /// Do not protect the private key with a passphrase.
+        #[arg(long)]
+        no_passphrase: bool,
+    },
+    /// Sign the profile's commits and tags with its SSH key.
+    ///
+    /// Sets gpg.format=ssh, user.signingkey, commit.gpgsign and tag.gpgsign,
+    /// and trusts the key for the profile's email in a per-profile
+    /// allowed_signers file (gpg.ssh.allowedSignersFile).
+    Sign {
+        /// The profile to configure.
+        #[arg(value_name = "PROFILE_ID")]
+        id: ProfileId,
+
+        /// Sign with this key instead of the one in the profile's core.sshCommand.
+        #[arg(long, value_name = "PATH")]
+        key: Option<PathBuf>,
+    },
+    /// Print the public key a profile uses, for pasting into a forge.
+    Show {
+        /// The profile whose public key to print.
+        #[arg(value_name = "PROFILE_ID")]
+        id: ProfileId,
+    },
+}
+
+#[derive(Subcommand)]
+enum GpgAction {
+    /// Bind a secret key from the gpg keyring to a profile.
+    ///
+    /// Keys whose UIDs match the profile's email are listed to pick from;
+    /// the choice is written as user.signingkey with gpg.format=openpgp.
+    Link {
+        /// The profile to bind the key to.
+        #[arg(value_name = "PROFILE_ID")]
+        id: ProfileId,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Validate config.toml, the profile directory, profiles and rules.
+    Check,
+}
+
+#[derive(Subcommand)]
+enum GuardAction {
+    /// Install pre-commit and pre-push hooks in the current repository.
+    ///
+    /// Existing hooks are kept and run after the identity check.
+    Install,
+    /// Remove the guard hooks and restore any chained hooks.
+    Uninstall,
+    /// Fail if the author email does not match the expected profile.
+    ///
+    /// This is what the installed hooks run.
+    Check,
+}
+
 // --- 2. Main 函数 ---
 
+// `-C` and `--git-dir` are written before the command, as with git. Because
+// top-level arguments conflict with subcommands, clap only takes them after a
+// subcommand's name, so they are moved behind it: `naj -C dir status` is parsed
+// as `naj status -C dir`.
+fn hoist_global_options(args: Vec<OsString>) -> Vec<OsString> {
+    let mut globals = Vec::new();
+    let mut i = 1;
+    while let Some(arg) = args.get(i).and_then(|a| a.to_str()) {
+        let takes_value = arg == "-C" || arg == "--git-dir";
+        if !(takes_value || arg.starts_with("-C") || arg.starts_with("--git-dir=")) {
+            break;
+        }
+        let end = if takes_value { i + 2 } else { i + 1 }.min(args.len());
+        globals.extend_from_slice(&args[i..end]);
+        i = end;
+    }
+
+    let cmd = Cli::command();
+    let is_subcommand = args.get(i).and_then(|a| a.to_str()).is_some_and(|name| {
+        cmd.get_subcommands()
+            .any(|sub| sub.get_name() == name || sub.get_all_aliases().any(|a| a == name))
+    });
+    if globals.is_empty() || !is_subcommand {
+        return args;
+    }
+
+    let mut hoisted = vec![args[0].clone(), args[i].clone()];
+    hoisted.extend(globals);
+    hoisted.extend_from_slice(&args[i + 1..]);
+    hoisted
+}
+
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let cli = Cli::parse_from(hoist_global_options(std::env::args_os().collect()));
 
     // 🚀 优先处理补全生成 (不加载配置，速度最快)
     if let Some(shell) = cli.completion {
@@ -78,9 +427,77 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    // 配置检查需要自行加载配置，以便报告解析错误
+    if let Some(Commands::Config {
+        action: ConfigAction::Check,
+    }) = cli.command
+    {
+        return manage::check_config();
+    }
+
     // 加载配置
     let config = config::load_config()?;
 
+    // 目标仓库: -C 指定的目录，默认为当前目录
+    let repo = cli.directory.clone().unwrap_or_else(|| PathBuf::from("."));
+    if let Some(git_dir) = &cli.git_dir {
+        // 只传给针对目标仓库的 git 命令；相对路径相对于 -C，先转为绝对路径
+        git::set_git_dir_override(&repo, std::env::current_dir()?.join(&repo).join(git_dir));
+    }
+
+    // 处理子命令
+    if let Some(command) = cli.command {
+        match command {
+            Commands::Auto {
+                force,
+                strategy,
+                dry_run,
+                format,
+                git_args,
+            } => git::run_auto(
+                &config,
+                &repo,
+                &git_args,
+                force,
+                strategy,
+                dry_run.then_some(format),
+            )?,
+            Commands::Audit { dir, fix, force } => {
+                audit::run_audit(&config, &repo.join(dir), fix, force)?
+            }
+            Commands::Status => status::run_status(&config, &repo)?,
+            Commands::Show { id } => manage::show_profile(&config, &id)?,
+            Commands::Key { action } => match action {
+                KeyAction::Gen { id, no_passphrase } => {
+                    keys::generate(&config, &id, no_passphrase)?
+                }
+                KeyAction::Show { id } => keys::show(&config, &id)?,
+                KeyAction::Sign { id, key } => keys::setup_signing(&config, &id, key.as_deref())?,
+            },
+            Commands::Gpg { action } => match action {
+                GpgAction::Link { id } => gpg::link(&config, &id)?,
+            },
+            Commands::Hosts { action } => match action {
+                HostsAction::Apply => hosts::apply_hosts(&config)?,
+                HostsAction::Remove => hosts::remove_hosts(&config)?,
+                HostsAction::List => hosts::list_hosts(&config)?,
+            },
+            Commands::History => history::run_history(&repo)?,
+            Commands::Undo { entry } => history::run_undo(&config, &repo, entry)?,
+            Commands::Guard { action } => match action {
+                GuardAction::Install => guard::install(&repo)?,
+                GuardAction::Uninstall => guard::uninstall(&repo)?,
+                GuardAction::Check => guard::run_check(&config, &repo)?,
+            },
+            Commands::Config { .. } => unreachable!("handled before loading config"),
+            Commands::Rules { action } => match action {
+                RulesAction::Apply => rules::apply_rules(&config)?,
+                RulesAction::List => rules::list_rules(&config)?,
+            },
+        }
+        return Ok(());
+    }
+
     // 处理 List
     if cli.list {
         manage::list_profiles(&config)?;
@@ -90,7 +507,8 @@ fn main() -> Result<()> {
     // 处理 Create
     if let Some(args) = cli.create {
         if args.len() == 3 {
-            manage::create_profile(&config, &args[0], &args[1], &args[2])?;
+            let id: ProfileId = args[2].parse()?;
+            manage::create_profile(&config, &args[0], &args[1], &id, cli.extends.as_ref())?;
         }
         return Ok(());
     }
@@ -101,13 +519,59 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    // 处理 Edit
+    if let Some(id) = cli.edit {
+        manage::edit_profile(&config, &id)?;
+        return Ok(());
+    }
+
     // 处理核心逻辑: Switch / Setup / Exec
-    if let Some(profile_id) = cli.profile_id {
+    if let (Some(profile_id), Some(dir)) = (&cli.profile_id, &cli.recursive) {
+        // 批量切换目录下的所有仓库
+        git::run_recursive(
+            &config,
+            profile_id,
+            &repo.join(dir),
+            cli.force,
+            cli.strategy,
+            cli.dry_run.then_some(cli.format),
+        )?;
+    } else if let Some(profile_id) = cli.profile_id {
+        // 按 worktree 绑定时先启用 extensions.worktreeConfig
+        if cli.worktree {
+            git::enable_worktree_config(&config, &repo)?;
+        }
+
         // 把 profile_id 和剩下的 git_args 传给 git::run
-        git::run(&config, &profile_id, &cli.git_args, cli.force)?;
+        git::run(
+            &config,
+            &repo,
+            &profile_id,
+            &cli.git_args,
+            cli.force,
+            cli.strategy,
+            cli.dry_run.then_some(cli.format),
+        )?;
+
+        // 同步切换所有已初始化的子模块
+        if cli.submodules {
+            git::run_submodules(
+                &config,
+                &repo,
+                &profile_id,
+                cli.force,
+                cli.strategy,
+                cli.dry_run.then_some(cli.format),
+            )?;
+        }
     } else {
         // 如果没有 profile_id 也没有 flag，打印帮助
-        if !cli.list && cli.create.is_none() && cli.remove.is_none() && cli.completion.is_none() {
+        if !cli.list
+            && cli.create.is_none()
+            && cli.remove.is_none()
+            && cli.edit.is_none()
+            && cli.completion.is_none()
+        {
             use clap::CommandFactory;
             Cli::command().print_help()?;
         }
@@ -1,41 +1,59 @@
-use crate::config::NajConfig;
-use crate::utils::expand_path;
-use anyhow::{bail, Context, Result};
+use crate::config::{get_config_root, load_config, NajConfig};
+use crate::git::{profile_file, read_profile_config, read_profile_entries};
+use crate::gitconfig::{resolve_include_path, GitConfig};
+use crate::profile::{join_ids, ProfileId};
+use crate::utils::{edit_distance, expand_path};
+use anyhow::{anyhow, bail, Context, Result};
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
 use std::process::Command;
 
-fn get_profile_path(config: &NajConfig, id: &str) -> Result<PathBuf> {
-    let profile_dir = expand_path(&config.profile_dir)?;
-    Ok(profile_dir.join(format!("{}.gitconfig", id)))
-}
-
-pub fn create_profile(config: &NajConfig, name: &str, email: &str, id: &str) -> Result<()> {
-    let file_path = get_profile_path(config, id)?;
+// `parent` makes the new profile extend an existing one: the parent is pulled in
+// through a relative `[include]` at the top, so git itself resolves it and the
+// new profile's own keys win.
+pub fn create_profile(
+    config: &NajConfig,
+    name: &str,
+    email: &str,
+    id: &ProfileId,
+    parent: Option<&ProfileId>,
+) -> Result<()> {
+    id.check_not_reserved()?;
+    let file_path = profile_file(config, id)?;
 
     if file_path.exists() {
         bail!("Profile '{}' already exists", id);
     }
+    if let Some(parent) = parent {
+        if !profile_file(config, parent)?.exists() {
+            bail!("Cannot extend unknown profile '{}'", parent);
+        }
+    }
 
     // Ensure dir exists (it typically should from init, but good to be safe)
     if let Some(parent) = file_path.parent() {
         fs::create_dir_all(parent)?;
     }
 
-    let content = format!(
+    let mut content = String::new();
+    if let Some(parent) = parent {
+        content.push_str(&format!("[include]\n    path = {}.gitconfig\n", parent));
+    }
+    content.push_str(&format!(
         "[user]\n    name = {}\n    email = {}\n    # signingkey = \n",
         name, email
-    );
+    ));
     fs::write(&file_path, content).with_context(|| format!("Failed to create profile {}", id))?;
     println!("Created profile '{}'", id);
     Ok(())
 }
 
-pub fn remove_profile(config: &NajConfig, id: &str) -> Result<()> {
-    let file_path = get_profile_path(config, id)?;
+pub fn remove_profile(config: &NajConfig, id: &ProfileId) -> Result<()> {
+    let file_path = profile_file(config, id)?;
 
     if !file_path.exists() {
-        bail!("Profile '{}' does not exist", id);
+        return Err(does_not_exist(config, id));
     }
 
     fs::remove_file(&file_path).with_context(|| format!("Failed to remove profile {}", id))?;
@@ -43,27 +61,230 @@ pub fn remove_profile(config: &NajConfig, id: &str) -> Result<()> {
     Ok(())
 }
 
-#[allow(dead_code)]
-pub fn edit_profile(config: &NajConfig, id: &str) -> Result<()> {
-    let file_path = get_profile_path(config, id)?;
+// Opens a profile in $VISUAL / $EDITOR and validates it once the editor exits.
+// A broken profile is reported, and the editor re-opened if the user wants to.
+pub fn edit_profile(config: &NajConfig, id: &ProfileId) -> Result<()> {
+    let file_path = profile_file(config, id)?;
 
     if !file_path.exists() {
-        bail!("Profile '{}' does not exist", id);
+        return Err(does_not_exist(config, id));
     }
 
-    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+
+    loop {
+        // The editor may carry its own arguments, e.g. `code --wait`
+        let mut parts = editor.split_whitespace();
+        let program = parts.next().unwrap_or("vi");
+        let status = Command::new(program)
+            .args(parts)
+            .arg(&file_path)
+            .status()
+            .with_context(|| format!("Failed to launch editor '{}'", editor))?;
 
-    let status = Command::new(&editor)
-        .arg(&file_path)
-        .status()
-        .with_context(|| format!("Failed to launch editor '{}'", editor))?;
+        if !status.success() {
+            bail!("Editor exited with non-zero status");
+        }
+
+        let err = match validate_profile(config, id) {
+            Ok(()) => {
+                println!("Profile '{}' is valid", id);
+                return Ok(());
+            }
+            Err(err) => err.context(format!("Profile '{}' is invalid", id)),
+        };
 
-    if !status.success() {
-        bail!("Editor exited with non-zero status");
+        // Without a terminal there is nobody to ask
+        if !io::stdin().is_terminal() {
+            return Err(err);
+        }
+        eprintln!("{:#}", err);
+        if !confirm("Re-open the editor?")? {
+            return Err(err);
+        }
     }
+}
+
+// Checks that a profile parses and that its inheritance chain resolves.
+fn validate_profile(config: &NajConfig, id: &ProfileId) -> Result<()> {
+    profile_ancestors(config, id)?;
+    read_profile_config(&profile_file(config, id)?)?;
     Ok(())
 }
 
+// Asks a yes/no question on the terminal; an empty answer means yes.
+fn confirm(question: &str) -> Result<bool> {
+    eprint!("{} [Y/n] ", question);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim().to_ascii_lowercase();
+    Ok(answer.is_empty() || answer == "y" || answer == "yes")
+}
+
+// Prints every key a profile resolves to, noting keys inherited from a parent.
+pub fn show_profile(config: &NajConfig, id: &ProfileId) -> Result<()> {
+    let file_path = profile_file(config, id)?;
+    if !file_path.exists() {
+        return Err(does_not_exist(config, id));
+    }
+
+    let parents = profile_parents(config, id)?;
+    if parents.is_empty() {
+        println!("Profile: {}", id);
+    } else {
+        println!("Profile: {} (extends {})", id, join_ids(&parents, ", "));
+    }
+    println!("File:    {}", file_path.display());
+    println!();
+
+    let entries = read_profile_entries(&file_path)?;
+    if entries.is_empty() {
+        println!("(no keys)");
+        return Ok(());
+    }
+
+    let width = entries.iter().map(|(_, k, _)| k.len()).max().unwrap_or(0);
+    for (origin, key, value) in entries {
+        if same_file(&origin, &file_path) {
+            println!("{:width$}  {}", key, value, width = width);
+        } else {
+            let from = origin
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            println!("{:width$}  {}  (from {})", key, value, from, width = width);
+        }
+    }
+    Ok(())
+}
+
+// Returns the IDs of all profiles in the profile directory, sorted by name.
+// Files whose name is not a valid profile ID are not profiles.
+pub fn profile_ids(config: &NajConfig) -> Result<Vec<ProfileId>> {
+    let profile_dir = expand_path(&config.profile_dir)?;
+    let mut ids = Vec::new();
+
+    if !profile_dir.exists() {
+        return Ok(ids);
+    }
+
+    for entry in fs::read_dir(profile_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "gitconfig") {
+            if let Some(Ok(id)) = path.file_stem().map(|s| s.to_string_lossy().parse()) {
+                ids.push(id);
+            }
+        }
+    }
+    ids.sort();
+    Ok(ids)
+}
+
+fn does_not_exist(config: &NajConfig, id: &ProfileId) -> anyhow::Error {
+    match similar_profiles(config, id) {
+        Ok(similar) if !similar.is_empty() => anyhow!(
+            "Profile '{}' does not exist. Did you mean '{}'?",
+            id,
+            join_ids(&similar, "' or '")
+        ),
+        _ => anyhow!("Profile '{}' does not exist", id),
+    }
+}
+
+// Existing profiles whose ID is a likely typo of `id`, closest first.
+pub fn similar_profiles(config: &NajConfig, id: &ProfileId) -> Result<Vec<ProfileId>> {
+    let wanted = id.as_str().to_ascii_lowercase();
+    let mut scored: Vec<(usize, ProfileId)> = profile_ids(config)?
+        .into_iter()
+        .map(|candidate| {
+            let distance = edit_distance(&wanted, &candidate.as_str().to_ascii_lowercase());
+            (distance, candidate)
+        })
+        .filter(|(distance, candidate)| {
+            *distance <= 2 && *distance < candidate.as_str().len().max(wanted.len()) / 2 + 1
+        })
+        .collect();
+    scored.sort();
+    Ok(scored.into_iter().map(|(_, id)| id).collect())
+}
+
+// Direct parents of a profile: its `include.path` entries that point at other
+// profiles in the profile directory.
+pub fn profile_parents(config: &NajConfig, id: &ProfileId) -> Result<Vec<ProfileId>> {
+    let profile_dir = expand_path(&config.profile_dir)?;
+    let file_path = profile_file(config, id)?;
+    let profile = GitConfig::load(&file_path)?;
+
+    let mut parents = Vec::new();
+    for include in profile.get_all("include.path") {
+        let target = resolve_include_path(include, Some(&profile_dir))?;
+        let in_profile_dir = target
+            .parent()
+            .is_some_and(|dir| same_file(dir, &profile_dir));
+        if !in_profile_dir || target.extension().is_none_or(|ext| ext != "gitconfig") {
+            continue;
+        }
+        let Some(Ok(parent)) = target
+            .file_stem()
+            .map(|s| s.to_string_lossy().parse::<ProfileId>())
+        else {
+            continue;
+        };
+        if !target.exists() {
+            bail!("Profile '{}' extends unknown profile '{}'", id, parent);
+        }
+        parents.push(parent);
+    }
+    Ok(parents)
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+// All profiles `id` inherits from, farthest ancestor first. Fails on cycles.
+pub fn profile_ancestors(config: &NajConfig, id: &ProfileId) -> Result<Vec<ProfileId>> {
+    fn visit(
+        config: &NajConfig,
+        chain: &mut Vec<ProfileId>,
+        out: &mut Vec<ProfileId>,
+    ) -> Result<()> {
+        let Some(id) = chain.last().cloned() else {
+            return Ok(());
+        };
+        for parent in profile_parents(config, &id)? {
+            if chain.contains(&parent) {
+                bail!(
+                    "Profile inheritance cycle: {} -> {}",
+                    join_ids(chain, " -> "),
+                    parent
+                );
+            }
+            if out.contains(&parent) {
+                continue;
+            }
+            chain.push(parent.clone());
+            visit(config, chain, out)?;
+            chain.pop();
+            out.push(parent);
+        }
+        Ok(())
+    }
+
+    let mut ancestors = Vec::new();
+    visit(config, &mut vec![id.clone()], &mut ancestors)?;
+    Ok(ancestors)
+}
+
 pub fn list_profiles(config: &NajConfig) -> Result<()> {
     let profile_dir = expand_path(&config.profile_dir)?;
 
@@ -75,14 +296,86 @@ pub fn list_profiles(config: &NajConfig) -> Result<()> {
         return Ok(());
     }
 
-    for entry in fs::read_dir(profile_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_file() && path.extension().map_or(false, |ext| ext == "gitconfig") {
-            if let Some(stem) = path.file_stem() {
-                println!("{}", stem.to_string_lossy());
+    for id in profile_ids(config)? {
+        match profile_ancestors(config, &id) {
+            Ok(ancestors) if ancestors.is_empty() => println!("{}", id),
+            Ok(_) => println!(
+                "{} (extends {})",
+                id,
+                join_ids(&profile_parents(config, &id)?, ", ")
+            ),
+            Err(e) => println!("{} (error: {})", id, e),
+        }
+    }
+    Ok(())
+}
+
+// Validates the whole configuration: config.toml itself (strict parsing happens
+// in load_config), the profile directory, every profile file and every rule.
+pub fn check_config() -> Result<()> {
+    let config_path = get_config_root()?.join("config.toml");
+    // load_config would write a default config; a check must not change anything
+    if !config_path.exists() {
+        println!("error  {:?} does not exist", config_path);
+        bail!("Configuration check failed with 1 problem(s)");
+    }
+    let config = load_config()?;
+    println!("ok     {:?} parsed", config_path);
+    println!(
+        "ok     strategies: switch = {}, clone = {}",
+        config.strategies.switch, config.strategies.clone
+    );
+
+    let mut problems = 0;
+    let profile_dir = expand_path(&config.profile_dir)?;
+    if profile_dir.is_dir() {
+        println!("ok     profile_dir {:?} exists", profile_dir);
+    } else {
+        println!("error  profile_dir {:?} does not exist", profile_dir);
+        problems += 1;
+    }
+
+    let ids = profile_ids(&config)?;
+    for id in &ids {
+        let path = profile_file(&config, id)?;
+        let resolved = profile_ancestors(&config, id).and_then(|_| read_profile_config(&path));
+        match resolved {
+            Ok(_) => println!("ok     profile '{}'", id),
+            Err(e) => {
+                println!("error  profile '{}' is invalid: {}", id, e);
+                problems += 1;
             }
         }
     }
+
+    let rule_refs = config
+        .rules
+        .iter()
+        .map(|r| (format!("rule '{}'", r.path), &r.profile))
+        .chain(
+            config
+                .url_rules
+                .iter()
+                .map(|r| (format!("url rule '{}'", r.pattern), &r.profile)),
+        )
+        .chain(
+            config
+                .host_aliases
+                .iter()
+                .map(|a| (format!("host alias '{}'", a.alias()), &a.profile)),
+        );
+    for (label, profile) in rule_refs {
+        if ids.contains(profile) {
+            println!("ok     {} -> '{}'", label, profile);
+        } else {
+            println!("error  {} refers to unknown profile '{}'", label, profile);
+            problems += 1;
+        }
+    }
+
+    if problems > 0 {
+        bail!("Configuration check failed with {} problem(s)", problems);
+    }
+    println!("Configuration is valid");
     Ok(())
 }
@@ -0,0 +1,174 @@
+use crate::config::{NajConfig, SwitchStrategy};
+use crate::git::{get_profile_dir, is_profile_include, read_profile_config, switch_config_path};
+use crate::gitconfig::GitConfig;
+use crate::hosts;
+use crate::profile::ProfileId;
+use crate::sanitizer;
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
+pub enum PlanFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Serialize)]
+pub struct InjectedKey {
+    pub key: String,
+    pub value: String,
+}
+
+// What a switch would change in the local config, computed without touching it.
+#[derive(Serialize)]
+pub struct SwitchPlan {
+    pub profile: ProfileId,
+    pub strategy: SwitchStrategy,
+    pub config: String,
+    pub remove_sections: Vec<String>,
+    pub unset_keys: Vec<String>,
+    pub remove_includes: Vec<String>,
+    pub add_includes: Vec<String>,
+    pub inject_keys: Vec<InjectedKey>,
+}
+
+impl SwitchPlan {
+    pub fn build(
+        naj_config: &NajConfig,
+        repo: &Path,
+        profile_id: &ProfileId,
+        profile_path: &Path,
+        strategy: SwitchStrategy,
+    ) -> Result<SwitchPlan> {
+        let profile_dir = get_profile_dir(naj_config)?;
+        let config_path = switch_config_path(repo)?;
+        let local = if config_path.exists() {
+            GitConfig::load(&config_path)?
+        } else {
+            GitConfig::default()
+        };
+
+        let mut plan = SwitchPlan {
+            profile: profile_id.clone(),
+            strategy,
+            config: config_path
+                .canonicalize()
+                .unwrap_or(config_path)
+                .to_string_lossy()
+                .to_string(),
+            remove_sections: Vec::new(),
+            unset_keys: Vec::new(),
+            remove_includes: Vec::new(),
+            add_includes: Vec::new(),
+            inject_keys: Vec::new(),
+        };
+
+        let has_section = |section: &str| {
+            local
+                .entries()
+                .any(|e| e.section == section && e.subsection.is_none())
+        };
+
+        let hard = matches!(
+            strategy,
+            SwitchStrategy::IncludeHard | SwitchStrategy::OverrideHard
+        );
+        if hard {
+            // Mirrors the sanitize step of a Hard switch
+            for section in sanitizer::BLACKLIST_SECTIONS.iter().chain(&["include"]) {
+                if has_section(section) {
+                    plan.remove_sections.push(section.to_string());
+                }
+            }
+            plan.remove_sections
+                .extend(sanitizer::blacklisted_subsections(&local));
+            for key in sanitizer::BLACKLIST_KEYS {
+                if !local.get_all(key).is_empty() {
+                    plan.unset_keys.push(key.to_string());
+                }
+            }
+        }
+
+        // Every switch drops the URL rewrites to host aliases
+        plan.remove_sections.extend(hosts::alias_rewrite_sections(
+            &local,
+            &hosts::managed_aliases(naj_config)?,
+        ));
+
+        // A Hard switch drops every include; otherwise only Naj profile links go
+        for include in local.get_all("include.path") {
+            if hard || is_profile_include(include, &profile_dir) {
+                plan.remove_includes.push(include.to_string());
+            }
+        }
+
+        match strategy {
+            SwitchStrategy::IncludeSoft | SwitchStrategy::IncludeHard => {
+                plan.add_includes
+                    .push(profile_path.to_string_lossy().to_string());
+            }
+            SwitchStrategy::OverrideSoft | SwitchStrategy::OverrideHard => {
+                plan.inject_keys = read_profile_config(profile_path)?
+                    .into_iter()
+                    .map(|(key, value)| InjectedKey { key, value })
+                    .collect();
+            }
+        }
+
+        Ok(plan)
+    }
+
+    pub fn print(&self, format: PlanFormat) -> Result<()> {
+        match format {
+            PlanFormat::Json => println!("{}", serde_json::to_string_pretty(self)?),
+            PlanFormat::Text => self.print_text(),
+        }
+        Ok(())
+    }
+
+    fn print_text(&self) {
+        println!(
+            "Plan for switching to profile '{}' ({}) in {}:",
+            self.profile, self.strategy, self.config
+        );
+
+        let mut empty = true;
+        let mut group = |title: &str, marker: char, items: Vec<String>| {
+            if items.is_empty() {
+                return;
+            }
+            empty = false;
+            println!("  {}:", title);
+            for item in items {
+                println!("    {} {}", marker, item);
+            }
+        };
+
+        group(
+            "Remove sections",
+            '-',
+            self.remove_sections
+                .iter()
+                .map(|s| format!("[{}]", s))
+                .collect(),
+        );
+        group("Unset keys", '-', self.unset_keys.clone());
+        group("Remove includes", '-', self.remove_includes.clone());
+        group("Add includes", '+', self.add_includes.clone());
+        group(
+            "Inject keys",
+            '+',
+            self.inject_keys
+                .iter()
+                .map(|k| format!("{} = {}", k.key, k.value))
+                .collect(),
+        );
+
+        if empty {
+            println!("  (nothing to change)");
+        }
+        println!("Dry run: no changes were made.");
+    }
+}
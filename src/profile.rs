@@ -0,0 +1,191 @@
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const MAX_LEN: usize = 64;
+
+// Names naj itself dispatches on (subcommands, and the git commands that
+// trigger setup mode), so a profile with one of them could never be selected.
+const RESERVED: &[&str] = &[
+    "audit", "auto", "clone", "config", "gpg", "guard", "help", "history", "hosts", "init", "key",
+    "rules", "show", "status", "undo", "whoami",
+];
+
+// A profile name that is safe to use as a file name inside the profile
+// directory: ASCII letters, digits, `-`, `_` and `.`, starting with a letter
+// or digit. Separators, `..` components and absolute paths cannot get through.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProfileId(String);
+
+// Git commands a profile must not be named after: `naj <id> <git args>` puts
+// the profile first, so `naj log` would be ambiguous and `naj commit -m x`
+// would read as a profile called "commit".
+const GIT_COMMANDS: &[&str] = &[
+    "add",
+    "am",
+    "annotate",
+    "apply",
+    "archive",
+    "bisect",
+    "blame",
+    "branch",
+    "bundle",
+    "cat-file",
+    "check-ignore",
+    "checkout",
+    "cherry",
+    "cherry-pick",
+    "citool",
+    "clean",
+    "clone",
+    "commit",
+    "config",
+    "describe",
+    "diff",
+    "difftool",
+    "fetch",
+    "format-patch",
+    "fsck",
+    "gc",
+    "grep",
+    "gui",
+    "help",
+    "init",
+    "instaweb",
+    "log",
+    "ls-files",
+    "ls-remote",
+    "ls-tree",
+    "maintenance",
+    "merge",
+    "mergetool",
+    "mv",
+    "notes",
+    "pull",
+    "push",
+    "range-diff",
+    "rebase",
+    "reflog",
+    "remote",
+    "repack",
+    "replace",
+    "request-pull",
+    "reset",
+    "restore",
+    "rev-list",
+    "rev-parse",
+    "revert",
+    "rm",
+    "send-email",
+    "shortlog",
+    "show",
+    "show-branch",
+    "sparse-checkout",
+    "stash",
+    "status",
+    "submodule",
+    "switch",
+    "tag",
+    "var",
+    "verify-commit",
+    "whatchanged",
+    "worktree",
+];
+
+// Whether `name` is a git command rather than something meant as a profile.
+pub fn is_git_command(name: &str) -> bool {
+    GIT_COMMANDS.contains(&name.to_ascii_lowercase().as_str())
+}
+
+impl ProfileId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    // The profile's file inside `profile_dir`.
+    pub fn file_in(&self, profile_dir: &Path) -> PathBuf {
+        profile_dir.join(format!("{}.gitconfig", self.0))
+    }
+
+    // New profiles must not shadow a naj command.
+    pub fn check_not_reserved(&self) -> Result<()> {
+        if RESERVED.contains(&self.0.to_ascii_lowercase().as_str()) {
+            bail!("'{}' is reserved by naj; pick another profile ID", self.0);
+        }
+        if is_git_command(&self.0) {
+            bail!(
+                "'{}' is a git command, so `naj {} ...` would be ambiguous; pick another profile ID",
+                self.0,
+                self.0
+            );
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ProfileId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.is_empty() {
+            bail!("profile ID must not be empty");
+        }
+        if s.len() > MAX_LEN {
+            bail!("profile ID '{}' is longer than {} characters", s, MAX_LEN);
+        }
+        if !s.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+            bail!("profile ID '{}' must start with a letter or digit", s);
+        }
+        if let Some(c) = s
+            .chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        {
+            return Err(anyhow!(
+                "profile ID '{}' contains '{}'; only letters, digits, '-', '_' and '.' are allowed",
+                s,
+                c
+            ));
+        }
+        Ok(ProfileId(s.to_string()))
+    }
+}
+
+pub fn join_ids(ids: &[ProfileId], sep: &str) -> String {
+    ids.iter()
+        .map(ProfileId::as_str)
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+impl fmt::Display for ProfileId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl PartialEq<str> for ProfileId {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl Serialize for ProfileId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ProfileId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
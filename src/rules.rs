@@ -0,0 +1,151 @@
+use crate::config::{get_config_root, DirRule, NajConfig, UrlRule};
+use crate::git::{get_profile_path, is_mocking, run_command};
+use crate::gitconfig::{gitdir_matches, GitConfig};
+use crate::utils::glob_match;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const MANAGED_HEADER: &str =
+    "# Managed by naj. Do not edit: run `naj rules apply` to regenerate.\n";
+
+// The generated includeIf blocks live in a naj-owned file that is linked once
+// from the global gitconfig, so regenerating never rewrites the user's own files.
+pub fn get_rules_path() -> Result<PathBuf> {
+    let path = get_config_root()?.join("rules.gitconfig");
+    if path.is_absolute() {
+        Ok(path)
+    } else {
+        Ok(std::env::current_dir()?.join(path))
+    }
+}
+
+pub fn apply_rules(config: &NajConfig) -> Result<()> {
+    let mut generated = GitConfig::default();
+
+    for rule in &config.rules {
+        let profile_path = get_profile_path(config, &rule.profile)
+            .with_context(|| format!("Invalid rule for path '{}'", rule.path))?;
+        let abs_profile_path = if profile_path.is_absolute() {
+            profile_path
+        } else {
+            std::env::current_dir()?.join(profile_path)
+        };
+
+        generated.add(
+            &format!("includeIf.gitdir:{}.path", rule.path),
+            &abs_profile_path.to_string_lossy(),
+        )?;
+    }
+    let content = format!("{}\n{}", MANAGED_HEADER, generated);
+
+    let rules_path = get_rules_path()?;
+    if is_mocking() {
+        eprintln!("[DRY-RUN] write {:?}:\n{}", rules_path, content);
+    } else {
+        fs::write(&rules_path, &content)
+            .with_context(|| format!("Failed to write rules file {:?}", rules_path))?;
+    }
+
+    ensure_global_include(&rules_path)?;
+    println!(
+        "Applied {} rule(s) via {:?}",
+        config.rules.len(),
+        rules_path
+    );
+    Ok(())
+}
+
+// Links the managed rules file from the global gitconfig exactly once.
+fn ensure_global_include(rules_path: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .args(["config", "--global", "--get-all", "include.path"])
+        .output()
+        .context("Failed to read global include.path")?;
+
+    let rules_str = rules_path.to_string_lossy();
+    let already_linked = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .any(|line| line.trim() == rules_str);
+
+    if !already_linked {
+        let mut cmd = Command::new("git");
+        cmd.args(["config", "--global", "--add", "include.path", &rules_str]);
+        run_command(&mut cmd)?;
+    }
+    Ok(())
+}
+
+// Returns the rule that git's includeIf would apply to `git_dir`. Later rules
+// win, mirroring git's "last include wins" semantics.
+pub fn match_dir_rule<'a>(config: &'a NajConfig, git_dir: &Path) -> Result<Option<&'a DirRule>> {
+    for rule in config.rules.iter().rev() {
+        if gitdir_matches(&rule.path, git_dir, None, false)? {
+            return Ok(Some(rule));
+        }
+    }
+    Ok(None)
+}
+
+pub fn list_rules(config: &NajConfig) -> Result<()> {
+    if config.rules.is_empty() {
+        println!("No rules configured");
+        return Ok(());
+    }
+    for rule in &config.rules {
+        println!("{} -> {}", rule.path, rule.profile);
+    }
+    Ok(())
+}
+
+// Reduces the many spellings of a remote URL to a comparable `host:path` form:
+// `git@github.com:acme/app.git`, `ssh://git@github.com:22/acme/app` and
+// `https://github.com/acme/app.git` all become `github.com:acme/app`.
+// Local paths (and `file://` URLs) are returned as plain paths.
+pub fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+    let url = url.strip_suffix('/').unwrap_or(url);
+    let url = url.strip_suffix(".git").unwrap_or(url);
+
+    let (host, path) = if let Some((_, rest)) = url.split_once("://") {
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let host = authority.rsplit('@').next().unwrap_or(authority);
+        let host = host.split(':').next().unwrap_or(host);
+        (host, path)
+    } else if url.starts_with('/') || url.starts_with('.') {
+        ("", url)
+    } else if let Some((authority, path)) = url.split_once(':') {
+        // scp-like syntax is only recognised when no slash precedes the colon
+        if authority.contains('/') {
+            ("", url)
+        } else {
+            (authority.rsplit('@').next().unwrap_or(authority), path)
+        }
+    } else if let Some((host, path)) = url.split_once('/') {
+        (host, path)
+    } else {
+        ("", url)
+    };
+
+    if host.is_empty() {
+        if url.contains("://") {
+            format!("/{}", path)
+        } else {
+            path.to_string()
+        }
+    } else {
+        format!("{}:{}", host.to_lowercase(), path)
+    }
+}
+
+// Returns the URL rule that matches the given remote URL. Later rules win,
+// like directory rules, so a specific pattern can follow a broader one.
+pub fn match_url_rule<'a>(config: &'a NajConfig, url: &str) -> Option<&'a UrlRule> {
+    let normalized = normalize_url(url);
+    config
+        .url_rules
+        .iter()
+        .rev()
+        .find(|rule| glob_match(&normalize_url(&rule.pattern), &normalized))
+}
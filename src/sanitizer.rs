@@ -1,7 +1,26 @@
+use crate::gitconfig::GitConfig;
+
 // Sections that often contain personal identity or security settings (e.g. GPG)
 // and should be cleared during Hard strategy switches for privacy.
 pub const BLACKLIST_SECTIONS: &[&str] = &["user", "author", "committer", "gpg"];
 
+// Blacklisted sections also come with a subsection, e.g. `[gpg "ssh"]`. Returns
+// the `section.subsection` names of those in `local`, in the form
+// `git config --remove-section` takes, so a Hard switch removes them too.
+pub fn blacklisted_subsections(local: &GitConfig) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for entry in local.entries() {
+        let Some(subsection) = &entry.subsection else {
+            continue;
+        };
+        let name = format!("{}.{}", entry.section, subsection);
+        if BLACKLIST_SECTIONS.contains(&entry.section.as_str()) && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
 // Specific keys that might leak sensitive paths or enforce signing protocols
 // that should be disabled when switching to a different project context.
 pub const BLACKLIST_KEYS: &[&str] = &[
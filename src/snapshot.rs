@@ -0,0 +1,70 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// A byte-for-byte copy of a config file taken before naj touches it.
+// `content` is None when the file did not exist at the time of the snapshot.
+pub struct ConfigSnapshot {
+    path: PathBuf,
+    content: Option<Vec<u8>>,
+}
+
+impl ConfigSnapshot {
+    pub fn take(path: &Path) -> Result<Self> {
+        let content = match fs::read(path) {
+            Ok(bytes) => Some(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to snapshot {:?}", path));
+            }
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            content,
+        })
+    }
+
+    // A snapshot holding previously saved content, e.g. from the history journal.
+    pub fn with_content(path: &Path, content: Vec<u8>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            content: Some(content),
+        }
+    }
+
+    // A snapshot of a file that did not exist; restoring it deletes the file.
+    pub fn absent(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            content: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn content(&self) -> Option<&[u8]> {
+        self.content.as_deref()
+    }
+
+    // Puts the file back exactly as it was. The content is written to a
+    // sibling file first and renamed over, so a crash never leaves it torn.
+    pub fn restore(&self) -> Result<()> {
+        let Some(content) = &self.content else {
+            if self.path.exists() {
+                fs::remove_file(&self.path)
+                    .with_context(|| format!("Failed to remove {:?}", self.path))?;
+            }
+            return Ok(());
+        };
+
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".naj-restore");
+        let tmp = PathBuf::from(tmp);
+        fs::write(&tmp, content).with_context(|| format!("Failed to write {:?}", tmp))?;
+        fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to restore {:?}", self.path))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+use crate::config::NajConfig;
+use crate::git::{
+    ensure_git_repo, get_profile_dir, get_profile_path, git_in, local_profile_includes,
+    read_profile_config,
+};
+use crate::guard::rule_profile;
+use crate::manage::{profile_ancestors, profile_ids};
+use crate::profile::{join_ids, ProfileId};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+// Keys that make up the identity naj is responsible for.
+pub const IDENTITY_KEYS: &[&str] = &[
+    "user.name",
+    "user.email",
+    "user.signingkey",
+    "core.sshCommand",
+];
+
+// Where git found the effective value of a key.
+pub struct Origin {
+    pub scope: String,
+    pub file: String,
+    pub value: String,
+}
+
+struct Profile {
+    id: ProfileId,
+    path: PathBuf,
+    entries: Vec<(String, String)>,
+    // Profiles this one extends, farthest first
+    ancestors: Vec<ProfileId>,
+}
+
+impl Profile {
+    fn value_of(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+// Asks git for the effective value of `key` together with its scope and file.
+pub fn effective_origin(repo: &Path, key: &str) -> Result<Option<Origin>> {
+    let output = git_in(repo)
+        .args(["config", "--show-scope", "--show-origin", "--get", key])
+        .output()
+        .with_context(|| format!("Failed to read effective value of {}", key))?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.trim_end_matches('\n');
+    let mut parts = line.splitn(3, '\t');
+    let (Some(scope), Some(file), Some(value)) = (parts.next(), parts.next(), parts.next()) else {
+        return Ok(None);
+    };
+
+    Ok(Some(Origin {
+        scope: scope.to_string(),
+        file: file.strip_prefix("file:").unwrap_or(file).to_string(),
+        value: value.to_string(),
+    }))
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn load_profiles(config: &NajConfig) -> Result<Vec<Profile>> {
+    let mut profiles = Vec::new();
+    for id in profile_ids(config)? {
+        let path = get_profile_path(config, &id)?;
+        let entries = read_profile_config(&path).unwrap_or_default();
+        let ancestors = profile_ancestors(config, &id).unwrap_or_default();
+        profiles.push(Profile {
+            id,
+            path,
+            entries,
+            ancestors,
+        });
+    }
+    Ok(profiles)
+}
+
+// Returns the IDs of the profiles included by the local config, plus any
+// include paths that look like Naj profiles but no longer exist.
+pub fn bound_profiles(config: &NajConfig, repo: &Path) -> Result<(Vec<ProfileId>, Vec<String>)> {
+    let profile_dir = get_profile_dir(config)?;
+    let profiles = load_profiles(config)?;
+    let mut bound = Vec::new();
+    let mut missing = Vec::new();
+    for include in local_profile_includes(repo, &profile_dir)? {
+        let include_path = Path::new(&include);
+        match profiles.iter().find(|p| same_file(&p.path, include_path)) {
+            Some(p) => bound.push(p.id.clone()),
+            None => missing.push(include),
+        }
+    }
+    Ok((bound, missing))
+}
+
+// The profile the effective user.email comes from when git reads that profile
+// through a global include, e.g. the includeIf blocks of `naj rules apply`.
+// It is labelled "rule" when the directory or URL rules select it (or a
+// profile extending it), "global" otherwise. Local bindings are not covered.
+pub fn included_profile(
+    config: &NajConfig,
+    repo: &Path,
+) -> Result<Option<(ProfileId, &'static str)>> {
+    let Some(origin) = effective_origin(repo, "user.email")? else {
+        return Ok(None);
+    };
+    if matches!(origin.scope.as_str(), "local" | "worktree" | "command") {
+        return Ok(None);
+    }
+    let profiles = load_profiles(config)?;
+    let Some(owner) = profiles
+        .iter()
+        .find(|p| same_file(&p.path, Path::new(&origin.file)))
+    else {
+        return Ok(None);
+    };
+
+    if let Some((rule_id, _)) = rule_profile(config, repo)? {
+        let selected = profiles.iter().find(|p| p.id == rule_id);
+        if selected.is_some_and(|p| p.id == owner.id || p.ancestors.contains(&owner.id)) {
+            return Ok(Some((rule_id, "rule")));
+        }
+    }
+    Ok(Some((owner.id.clone(), "global")))
+}
+
+pub fn run_status(config: &NajConfig, repo: &Path) -> Result<()> {
+    ensure_git_repo(repo)?;
+
+    let profiles = load_profiles(config)?;
+
+    // 1. Profiles bound through local include.path entries
+    let (bound, missing) = bound_profiles(config, repo)?;
+    for include in missing {
+        println!("⚠️  Local include points at a missing profile: {}", include);
+    }
+
+    let mut warnings = Vec::new();
+    if bound.len() > 1 {
+        warnings.push(format!(
+            "Multiple profiles are included: {}",
+            join_ids(&bound, ", ")
+        ));
+    }
+
+    // 2. Attribute every effective identity key to a profile (if any)
+    let mut rows = Vec::new();
+    let mut override_sources = Vec::new();
+    for key in IDENTITY_KEYS {
+        let Some(origin) = effective_origin(repo, key)? else {
+            rows.push((key.to_string(), "(unset)".to_string(), String::new()));
+            continue;
+        };
+
+        let included_from = profiles
+            .iter()
+            .find(|p| same_file(&p.path, Path::new(&origin.file)));
+
+        let (source, owners): (String, Vec<&str>) = if let Some(p) = included_from {
+            // A key inherited from a parent belongs to the bound child as well
+            let heir = profiles
+                .iter()
+                .find(|c| bound.contains(&c.id) && c.ancestors.contains(&p.id));
+            match heir {
+                Some(child) => (
+                    format!("{} include ({} via {})", origin.scope, p.id, child.id),
+                    vec![p.id.as_str(), child.id.as_str()],
+                ),
+                None => (
+                    format!("{} include ({})", origin.scope, p.id),
+                    vec![p.id.as_str()],
+                ),
+            }
+        } else {
+            let mut owners: Vec<&str> = profiles
+                .iter()
+                .filter(|p| p.value_of(key) == Some(origin.value.as_str()))
+                .map(|p| p.id.as_str())
+                .collect();
+            // An inherited value is credited to the most derived profile
+            let all_owners = owners.clone();
+            owners.retain(|o| {
+                !profiles.iter().any(|c| {
+                    all_owners.contains(&c.id.as_str()) && c.ancestors.iter().any(|a| a == *o)
+                })
+            });
+            // Override switches write to config.worktree when bound per worktree
+            let in_repo_config = matches!(origin.scope.as_str(), "local" | "worktree");
+            let source = if owners.is_empty() || !in_repo_config {
+                format!("{} {}", origin.scope, origin.file)
+            } else {
+                override_sources.extend(owners.iter().map(|o| o.to_string()));
+                format!("{} override ({})", origin.scope, owners.join("|"))
+            };
+            (source, owners)
+        };
+
+        for id in &bound {
+            let Some(profile) = profiles.iter().find(|p| &p.id == id) else {
+                continue;
+            };
+            if owners.contains(&id.as_str()) {
+                continue;
+            }
+            if !owners.is_empty() {
+                warnings.push(format!(
+                    "{} comes from profile '{}' but the repo includes '{}'",
+                    key,
+                    owners.join("|"),
+                    id
+                ));
+            } else if profile.value_of(key).is_some() {
+                warnings.push(format!(
+                    "{} from profile '{}' is shadowed by {} config ({})",
+                    key, id, origin.scope, origin.file
+                ));
+            }
+        }
+
+        rows.push((key.to_string(), origin.value, source));
+    }
+
+    // 3. Report
+    override_sources.sort();
+    override_sources.dedup();
+    if !bound.is_empty() {
+        println!("Profile: {} (include)", join_ids(&bound, ", "));
+    } else if let Some((id, how)) = included_profile(config, repo)? {
+        println!("Profile: {} ({})", id, how);
+    } else if override_sources.len() == 1 {
+        println!("Profile: {} (override)", override_sources[0]);
+    } else {
+        println!("Profile: (none)");
+    }
+    println!();
+
+    let width = IDENTITY_KEYS.iter().map(|k| k.len()).max().unwrap_or(0);
+    for (key, value, source) in rows {
+        if source.is_empty() {
+            println!("{:width$}  {}", key, value, width = width);
+        } else {
+            println!("{:width$}  {}  [{}]", key, value, source, width = width);
+        }
+    }
+
+    if !warnings.is_empty() {
+        println!("\n⚠️  WARNING: Identity mismatch detected!");
+        for warning in warnings {
+            println!("  - {}", warning);
+        }
+    }
+
+    Ok(())
+}
@@ -20,6 +20,66 @@ pub fn expand_path(path_str: &str) -> Result<PathBuf> {
     Ok(PathBuf::from(path_str))
 }
 
+// Minimal glob matcher for path-like patterns. `*` and `?` never cross a `/`,
+// while `**` spans any number of components (including none when written as `**/`).
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    fn matches(p: &[u8], t: &[u8]) -> bool {
+        match p {
+            [] => t.is_empty(),
+            [b'*', b'*', rest @ ..] => {
+                if let Some(after_slash) = rest.strip_prefix(b"/") {
+                    if matches(after_slash, t) {
+                        return true;
+                    }
+                }
+                (0..=t.len()).any(|i| matches(rest, &t[i..]))
+            }
+            [b'*', rest @ ..] => {
+                for i in 0..=t.len() {
+                    if matches(rest, &t[i..]) {
+                        return true;
+                    }
+                    if i < t.len() && t[i] == b'/' {
+                        break;
+                    }
+                }
+                false
+            }
+            [b'?', rest @ ..] => !t.is_empty() && t[0] != b'/' && matches(rest, &t[1..]),
+            [c, rest @ ..] => !t.is_empty() && t[0] == *c && matches(rest, &t[1..]),
+        }
+    }
+    matches(pattern.as_bytes(), text.as_bytes())
+}
+
+// Quotes `arg` for a POSIX shell unless it is made of harmless characters only.
+pub fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@%+,".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+// Levenshtein distance, used to suggest near-miss profile IDs.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            row.push((prev[j] + cost).min(prev[j + 1] + 1).min(row[j] + 1));
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
 #[cfg(debug_assertions)]
 #[macro_export]
 macro_rules! naj_debug {
@@ -0,0 +1,160 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()?;
+    assert!(output.status.success(), "git {:?} failed", args);
+    Ok(())
+}
+
+fn setup_env(
+) -> Result<(TempDir, std::path::PathBuf, std::path::PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+    fs::create_dir_all(temp_dir.path().join("src"))?;
+    // Rules match the resolved git dir, so the tree path must be canonical
+    let tree = temp_dir.path().join("src").canonicalize()?;
+
+    for (name, email, id) in [
+        ("Work User", "work@corp.com", "work"),
+        ("Me", "me@home.org", "personal"),
+    ] {
+        Command::new(env!("CARGO_BIN_EXE_naj"))
+            .env("NAJ_CONFIG_PATH", &config_path)
+            .args(["-c", name, email, id])
+            .assert()
+            .success();
+    }
+
+    let mut config = fs::read_to_string(config_path.join("config.toml"))?;
+    config.push_str(&format!(
+        "\n[[rules]]\npath = \"{}/work/**\"\nprofile = \"work\"\n",
+        tree.display()
+    ));
+    fs::write(config_path.join("config.toml"), config)?;
+
+    // work/app is bound to the wrong profile, work/lib is fine, a worktree of
+    // work/lib and a bare mirror sit next to them.
+    fs::create_dir_all(tree.join("work"))?;
+    git(&tree.join("work"), &["init", "app"])?;
+    git(&tree.join("work"), &["init", "lib"])?;
+    git(&tree, &["init", "--bare", "mirror.git"])?;
+    for (repo, id) in [("work/app", "personal"), ("work/lib", "work")] {
+        Command::new(env!("CARGO_BIN_EXE_naj"))
+            .env("NAJ_CONFIG_PATH", &config_path)
+            .arg(id)
+            .current_dir(tree.join(repo))
+            .assert()
+            .success();
+    }
+    let lib = tree.join("work/lib");
+    git(
+        &lib,
+        &[
+            "-c",
+            "user.name=x",
+            "-c",
+            "user.email=x@x",
+            "commit",
+            "--allow-empty",
+            "-m",
+            "init",
+        ],
+    )?;
+    git(&lib, &["worktree", "add", "../lib-wt"])?;
+
+    Ok((temp_dir, config_path, tree))
+}
+
+#[test]
+fn test_audit_reports_repositories() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, tree) = setup_env()?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["audit"])
+        .arg(&tree)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "{} (bare)",
+            tree.join("mirror.git").display()
+        )))
+        .stdout(predicate::str::contains(format!(
+            "{}\n  profile:  work (include)",
+            tree.join("work/lib-wt").display()
+        )))
+        .stdout(predicate::str::contains(
+            "bound to 'personal' but directory rule",
+        ))
+        .stdout(predicate::str::contains(
+            "Audited 4 repositories, 1 with findings",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_audit_fix_applies_rule_profile() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, tree) = setup_env()?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["audit", "--fix"])
+        .arg(&tree)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 fixed"));
+
+    let output = std::process::Command::new("git")
+        .args(["config", "user.email"])
+        .current_dir(tree.join("work/app"))
+        .output()?;
+    assert_eq!(String::from_utf8(output.stdout)?.trim(), "work@corp.com");
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["audit"])
+        .arg(&tree)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("0 with findings"));
+
+    Ok(())
+}
+
+#[test]
+fn test_audit_accepts_directory_rule_binding() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp_dir, config_path, tree) = setup_env()?;
+    let global_config = temp_dir.path().join("global.gitconfig");
+    git(&tree.join("work"), &["init", "svc"])?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .env("GIT_CONFIG_GLOBAL", &global_config)
+        .args(["rules", "apply"])
+        .assert()
+        .success();
+
+    // svc gets the work identity from the rule alone; no local include is needed
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .env("GIT_CONFIG_GLOBAL", &global_config)
+        .args(["audit", "--fix"])
+        .arg(tree.join("work/svc"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("profile:  work (rule)"))
+        .stdout(predicate::str::contains(
+            "Audited 1 repositories, 0 with findings",
+        ));
+    assert!(!fs::read_to_string(tree.join("work/svc/.git/config"))?.contains("include"));
+
+    Ok(())
+}
@@ -0,0 +1,188 @@
+use assert_cmd::Command;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let output = std::process::Command::new("git")
+        .args([
+            "-c",
+            "user.name=Setup",
+            "-c",
+            "user.email=setup@example.com",
+        ])
+        .args(args)
+        .current_dir(dir)
+        .output()?;
+    assert!(output.status.success(), "git {:?} failed", args);
+    Ok(())
+}
+
+fn git_get(repo_dir: &Path, key: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let output = std::process::Command::new("git")
+        .args(["config", "--get", key])
+        .current_dir(repo_dir)
+        .output()?;
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+// A profile and a source repository `upstream` with a `main` branch
+fn setup_env() -> Result<(TempDir, PathBuf, String), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-c", "Work User", "work@corp.com", "work"])
+        .assert()
+        .success();
+
+    let source = temp_dir.path().join("upstream");
+    std::fs::create_dir_all(&source)?;
+    git(&source, &["init", "-b", "main"])?;
+    git(&source, &["commit", "--allow-empty", "-m", "init"])?;
+
+    let url = source.to_string_lossy().to_string();
+    Ok((temp_dir, config_path, url))
+}
+
+#[test]
+fn test_clone_options_with_values() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp_dir, config_path, url) = setup_env()?;
+    let dest = temp_dir.path().join("dest");
+    std::fs::create_dir_all(&dest)?;
+
+    // `main` and `up` are option values, not the URL or the directory
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&dest)
+        .args(["work", "clone", "-b", "main", "-o", "up", "--reference"])
+        .args([&url, "--", &url])
+        .assert()
+        .success();
+    assert_eq!(
+        git_get(&dest.join("upstream"), "user.email")?,
+        "work@corp.com"
+    );
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&dest)
+        .args([
+            "work",
+            "clone",
+            "--separate-git-dir",
+            "store.git",
+            "-qbmain",
+        ])
+        .args([&url, "checkout"])
+        .assert()
+        .success();
+    assert_eq!(
+        git_get(&dest.join("checkout"), "user.email")?,
+        "work@corp.com"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_bare_clone_binds_name_dot_git() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp_dir, config_path, url) = setup_env()?;
+    let dest = temp_dir.path().join("dest");
+    std::fs::create_dir_all(&dest)?;
+
+    for flag in ["--bare", "--mirror"] {
+        let target = dest.join(flag.trim_start_matches('-'));
+        std::fs::create_dir_all(&target)?;
+        Command::new(env!("CARGO_BIN_EXE_naj"))
+            .env("NAJ_CONFIG_PATH", &config_path)
+            .current_dir(&target)
+            .args(["work", "clone", flag, &url])
+            .assert()
+            .success();
+        assert_eq!(
+            git_get(&target.join("upstream.git"), "user.email")?,
+            "work@corp.com"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_clone_fetches_with_profile_ssh_command() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp_dir, config_path, _) = setup_env()?;
+    let marker = temp_dir.path().join("ssh-called");
+
+    // A stand-in for ssh that records it was used, then fails the fetch
+    let fake_ssh = temp_dir.path().join("fake-ssh");
+    std::fs::write(
+        &fake_ssh,
+        format!("#!/bin/sh\necho \"$@\" > '{}'\nexit 1\n", marker.display()),
+    )?;
+    std::fs::set_permissions(
+        &fake_ssh,
+        std::os::unix::fs::PermissionsExt::from_mode(0o755),
+    )?;
+    let profile = config_path.join("profiles/work.gitconfig");
+    let mut content = std::fs::read_to_string(&profile)?;
+    content.push_str(&format!(
+        "[core]\n    sshCommand = {}\n",
+        fake_ssh.display()
+    ));
+    std::fs::write(&profile, content)?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .env("GIT_SSH_COMMAND", "false")
+        .current_dir(temp_dir.path())
+        .args(["work", "clone", "ssh://git@example.invalid/acme/app.git"])
+        .assert()
+        .failure();
+
+    let args = std::fs::read_to_string(&marker)?;
+    assert!(args.contains("example.invalid"));
+
+    Ok(())
+}
+
+#[test]
+fn test_init_binds_created_directory() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp_dir, config_path, _) = setup_env()?;
+    let dest = temp_dir.path().join("dest");
+    std::fs::create_dir_all(dest.join("templates"))?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&dest)
+        .args(["work", "init", "-b", "main", "newproj"])
+        .assert()
+        .success();
+    assert_eq!(
+        git_get(&dest.join("newproj"), "user.email")?,
+        "work@corp.com"
+    );
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&dest)
+        .args([
+            "work",
+            "init",
+            "--bare",
+            "--template",
+            "templates",
+            "store.git",
+        ])
+        .assert()
+        .success();
+    assert_eq!(
+        git_get(&dest.join("store.git"), "user.email")?,
+        "work@corp.com"
+    );
+
+    // The directory naj runs in is not a repository and stays untouched
+    assert!(!dest.join(".git").exists());
+
+    Ok(())
+}
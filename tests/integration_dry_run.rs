@@ -0,0 +1,82 @@
+use assert_cmd::Command;
+use std::fs;
+use tempfile::TempDir;
+
+fn setup_env(
+) -> Result<(TempDir, std::path::PathBuf, std::path::PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+    let repo_dir = temp_dir.path().join("repo");
+
+    fs::create_dir_all(&repo_dir)?;
+    std::process::Command::new("git")
+        .arg("init")
+        .current_dir(&repo_dir)
+        .output()?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-c", "Work User", "work@corp.com", "work"])
+        .assert()
+        .success();
+
+    // Hand-tuned local identity that a hard switch would wipe
+    for (key, value) in [("user.name", "Hand"), ("core.sshCommand", "ssh -i key")] {
+        std::process::Command::new("git")
+            .args(["config", key, value])
+            .current_dir(&repo_dir)
+            .output()?;
+    }
+
+    Ok((temp_dir, config_path, repo_dir))
+}
+
+#[test]
+fn test_dry_run_prints_plan_without_changes() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+    let before = fs::read(repo_dir.join(".git/config"))?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .args(["work", "-f", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("profile 'work' (INCLUDE)"))
+        .stdout(predicates::str::contains("Remove sections:\n    - [user]"))
+        .stdout(predicates::str::contains(
+            "Unset keys:\n    - core.sshCommand",
+        ))
+        .stdout(predicates::str::contains("Add includes:"))
+        .stdout(predicates::str::contains("work.gitconfig"));
+
+    assert_eq!(fs::read(repo_dir.join(".git/config"))?, before);
+    assert!(!repo_dir.join(".git/naj").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_dry_run_json_plan() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .args(["work", "-s", "OVERRIDE", "--dry-run", "--format", "json"])
+        .output()?;
+    assert!(output.status.success());
+
+    let plan: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(plan["profile"], "work");
+    assert_eq!(plan["strategy"], "OVERRIDE");
+    assert_eq!(plan["remove_sections"], serde_json::json!(["user"]));
+    assert_eq!(plan["unset_keys"], serde_json::json!(["core.sshCommand"]));
+    assert_eq!(plan["add_includes"], serde_json::json!([]));
+    assert_eq!(
+        plan["inject_keys"][1],
+        serde_json::json!({ "key": "user.email", "value": "work@corp.com" })
+    );
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn gpg_available() -> bool {
+    std::process::Command::new("gpg")
+        .arg("--version")
+        .output()
+        .is_ok()
+}
+
+fn gen_key(gnupg_home: &Path, uid: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let status = std::process::Command::new("gpg")
+        .env("GNUPGHOME", gnupg_home)
+        .args(["--batch", "--passphrase", "", "--quick-gen-key", uid])
+        .args(["ed25519", "sign", "never"])
+        .output()?
+        .status;
+    assert!(status.success(), "gpg could not generate a key");
+    Ok(())
+}
+
+fn fingerprints(gnupg_home: &Path, uid: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let output = std::process::Command::new("gpg")
+        .env("GNUPGHOME", gnupg_home)
+        .args(["--batch", "--with-colons", "--list-secret-keys", uid])
+        .output()?;
+    let mut fprs = Vec::new();
+    let mut primary = false;
+    for line in String::from_utf8(output.stdout)?.lines() {
+        let fields: Vec<&str> = line.split(':').collect();
+        match fields[0] {
+            "sec" => primary = true,
+            "ssb" => primary = false,
+            "fpr" if primary => {
+                fprs.push(fields[9].to_string());
+                primary = false;
+            }
+            _ => {}
+        }
+    }
+    Ok(fprs)
+}
+
+fn setup_env(
+) -> Result<(TempDir, std::path::PathBuf, std::path::PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+    let gnupg_home = temp_dir.path().join("gnupg");
+    fs::create_dir_all(&gnupg_home)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&gnupg_home, fs::Permissions::from_mode(0o700))?;
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-c", "Work User", "work@corp.com", "work"])
+        .assert()
+        .success();
+
+    Ok((temp_dir, config_path, gnupg_home))
+}
+
+#[test]
+fn test_gpg_link_picks_matching_key() -> Result<(), Box<dyn std::error::Error>> {
+    if !gpg_available() {
+        eprintln!("gpg not available, skipping");
+        return Ok(());
+    }
+    let (_temp, config_path, gnupg_home) = setup_env()?;
+    gen_key(&gnupg_home, "Work User <work@corp.com>")?;
+    gen_key(&gnupg_home, "Work Laptop <work@corp.com>")?;
+    gen_key(&gnupg_home, "Someone Else <else@corp.com>")?;
+    let laptop = fingerprints(&gnupg_home, "Work Laptop")?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .env("GNUPGHOME", &gnupg_home)
+        .args(["gpg", "link", "work"])
+        .write_stdin("2\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Work User <work@corp.com>"))
+        .stdout(predicate::str::contains("Someone Else").not());
+
+    let profile = fs::read_to_string(config_path.join("profiles/work.gitconfig"))?;
+    assert!(profile.contains(&format!("signingkey = {}", laptop[0])));
+    assert!(profile.contains("format = openpgp"));
+
+    Ok(())
+}
+
+#[test]
+fn test_switch_warns_about_missing_signing_key() -> Result<(), Box<dyn std::error::Error>> {
+    if !gpg_available() {
+        eprintln!("gpg not available, skipping");
+        return Ok(());
+    }
+    let (temp, config_path, gnupg_home) = setup_env()?;
+    let repo_dir = temp.path().join("repo");
+    fs::create_dir_all(&repo_dir)?;
+    std::process::Command::new("git")
+        .arg("init")
+        .current_dir(&repo_dir)
+        .output()?;
+    fs::write(
+        config_path.join("profiles/work.gitconfig"),
+        "[user]\n    email = work@corp.com\n    signingkey = 0123456789ABCDEF\n",
+    )?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .env("GNUPGHOME", &gnupg_home)
+        .arg("work")
+        .current_dir(&repo_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Signing key 0123456789ABCDEF is not in your gpg keyring",
+        ));
+
+    Ok(())
+}
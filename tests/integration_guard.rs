@@ -0,0 +1,143 @@
+use assert_cmd::Command;
+use std::fs;
+use tempfile::TempDir;
+
+fn setup_env(
+) -> Result<(TempDir, std::path::PathBuf, std::path::PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+    let repo_dir = temp_dir.path().join("repo");
+
+    fs::create_dir_all(&repo_dir)?;
+    std::process::Command::new("git")
+        .arg("init")
+        .current_dir(&repo_dir)
+        .output()?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-c", "Work User", "work@corp.com", "work"])
+        .assert()
+        .success();
+
+    Ok((temp_dir, config_path, repo_dir))
+}
+
+fn git_commit(
+    config_path: &std::path::Path,
+    repo_dir: &std::path::Path,
+) -> std::io::Result<std::process::Output> {
+    std::process::Command::new("git")
+        .env("NAJ_CONFIG_PATH", config_path)
+        .args(["commit", "--allow-empty", "-m", "guarded"])
+        .current_dir(repo_dir)
+        .output()
+}
+
+#[test]
+fn test_guard_blocks_mismatched_email() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("work")
+        .assert()
+        .success();
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .args(["guard", "install"])
+        .assert()
+        .success();
+
+    let hook = fs::read_to_string(repo_dir.join(".git/hooks/pre-commit"))?;
+    assert!(hook.contains("guard check"));
+    assert!(repo_dir.join(".git/hooks/pre-push").exists());
+
+    // Bound profile and effective identity agree
+    assert!(git_commit(&config_path, &repo_dir)?.status.success());
+
+    // A stray local email must be refused
+    std::process::Command::new("git")
+        .args(["config", "user.email", "me@home.org"])
+        .current_dir(&repo_dir)
+        .output()?;
+    let output = git_commit(&config_path, &repo_dir)?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("does not match profile 'work'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_guard_chains_existing_hook() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+    let hooks_dir = repo_dir.join(".git/hooks");
+    fs::create_dir_all(&hooks_dir)?;
+    fs::write(hooks_dir.join("pre-commit"), "#!/bin/sh\nexit 0\n")?;
+
+    // Installing twice must not chain the guard into itself
+    for _ in 0..2 {
+        Command::new(env!("CARGO_BIN_EXE_naj"))
+            .env("NAJ_CONFIG_PATH", &config_path)
+            .current_dir(&repo_dir)
+            .args(["guard", "install"])
+            .assert()
+            .success();
+    }
+
+    let chained = fs::read_to_string(hooks_dir.join("pre-commit.pre-naj"))?;
+    assert_eq!(chained, "#!/bin/sh\nexit 0\n");
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .args(["guard", "uninstall"])
+        .assert()
+        .success();
+
+    let restored = fs::read_to_string(hooks_dir.join("pre-commit"))?;
+    assert_eq!(restored, "#!/bin/sh\nexit 0\n");
+    assert!(!hooks_dir.join("pre-commit.pre-naj").exists());
+    assert!(!hooks_dir.join("pre-push").exists());
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_guard_hook_quotes_naj_path() -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (temp, config_path, repo_dir) = setup_env()?;
+    // The shell must not expand anything in the path of the naj binary
+    let bin_dir = temp.path().join("bin $HOME `true` \"x\"");
+    fs::create_dir_all(&bin_dir)?;
+    let naj = bin_dir.join("naj");
+    fs::copy(env!("CARGO_BIN_EXE_naj"), &naj)?;
+    fs::set_permissions(&naj, fs::Permissions::from_mode(0o755))?;
+
+    for args in [vec!["work"], vec!["guard", "install"]] {
+        Command::new(&naj)
+            .env("NAJ_CONFIG_PATH", &config_path)
+            .current_dir(&repo_dir)
+            .args(args)
+            .assert()
+            .success();
+    }
+
+    assert!(git_commit(&config_path, &repo_dir)?.status.success());
+
+    // The hook really ran naj: a stray email is still refused
+    std::process::Command::new("git")
+        .args(["config", "user.email", "me@home.org"])
+        .current_dir(&repo_dir)
+        .output()?;
+    let output = git_commit(&config_path, &repo_dir)?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("does not match profile 'work'"));
+
+    Ok(())
+}
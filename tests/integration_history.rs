@@ -0,0 +1,137 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+fn setup_env(
+) -> Result<(TempDir, std::path::PathBuf, std::path::PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+    let repo_dir = temp_dir.path().join("repo");
+
+    fs::create_dir_all(&repo_dir)?;
+    std::process::Command::new("git")
+        .arg("init")
+        .current_dir(&repo_dir)
+        .output()?;
+
+    for (id, name, email) in [
+        ("work", "Work User", "work@corp.com"),
+        ("personal", "Me", "me@home.org"),
+    ] {
+        Command::new(env!("CARGO_BIN_EXE_naj"))
+            .env("NAJ_CONFIG_PATH", &config_path)
+            .args(["-c", name, email, id])
+            .assert()
+            .success();
+    }
+
+    Ok((temp_dir, config_path, repo_dir))
+}
+
+fn naj(config_path: &std::path::Path, repo_dir: &std::path::Path, args: &[&str]) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_naj"));
+    cmd.env("NAJ_CONFIG_PATH", config_path)
+        .current_dir(repo_dir)
+        .args(args);
+    cmd
+}
+
+#[test]
+fn test_undo_restores_hand_tuned_config() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+
+    std::process::Command::new("git")
+        .args(["config", "user.signingkey", "ABCD1234"])
+        .current_dir(&repo_dir)
+        .output()?;
+    let hand_tuned = fs::read(repo_dir.join(".git/config"))?;
+
+    naj(&config_path, &repo_dir, &["personal"])
+        .assert()
+        .success();
+    let with_personal = fs::read(repo_dir.join(".git/config"))?;
+
+    // The hard switch wipes [user]; both previous states are journaled
+    naj(&config_path, &repo_dir, &["-f", "work"])
+        .assert()
+        .success();
+    assert!(!fs::read_to_string(repo_dir.join(".git/config"))?.contains("ABCD1234"));
+
+    naj(&config_path, &repo_dir, &["history"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("#2"))
+        .stdout(predicates::str::contains("profile: personal"))
+        .stdout(predicates::str::contains("before switch to 'work'"))
+        .stdout(predicates::str::contains("#1"));
+
+    naj(&config_path, &repo_dir, &["undo"]).assert().success();
+    assert_eq!(fs::read(repo_dir.join(".git/config"))?, with_personal);
+
+    naj(&config_path, &repo_dir, &["undo", "1"])
+        .assert()
+        .success();
+    assert_eq!(fs::read(repo_dir.join(".git/config"))?, hand_tuned);
+
+    // Only the state replaced by the last undo is left, under a fresh number
+    naj(&config_path, &repo_dir, &["history"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("#4"))
+        .stdout(predicates::str::contains("before undo to #1"))
+        .stdout(predicates::str::contains("#1 ").not());
+
+    // Undoing the undo brings the personal binding back
+    naj(&config_path, &repo_dir, &["undo"]).assert().success();
+    assert_eq!(fs::read(repo_dir.join(".git/config"))?, with_personal);
+
+    Ok(())
+}
+
+#[test]
+fn test_noop_switch_is_not_journaled() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+
+    naj(&config_path, &repo_dir, &["work"]).assert().success();
+    naj(&config_path, &repo_dir, &["work"]).assert().success();
+
+    naj(&config_path, &repo_dir, &["history"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("#1"))
+        .stdout(predicates::str::contains("#2").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_undo_first_worktree_switch_leaves_worktree_unbound(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+    let worktree_config = repo_dir.join(".git/config.worktree");
+
+    naj(&config_path, &repo_dir, &["--worktree", "work"])
+        .assert()
+        .success();
+    assert!(worktree_config.exists());
+
+    naj(&config_path, &repo_dir, &["history"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("#1"))
+        .stdout(predicates::str::contains("profile: (none)"));
+
+    naj(&config_path, &repo_dir, &["undo"]).assert().success();
+    assert!(!worktree_config.exists());
+    naj(&config_path, &repo_dir, &["status"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Profile: (none)"));
+
+    // The undo is journaled too, so it can be reverted
+    naj(&config_path, &repo_dir, &["undo"]).assert().success();
+    assert!(fs::read_to_string(&worktree_config)?.contains("work.gitconfig"));
+
+    Ok(())
+}
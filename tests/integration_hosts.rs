@@ -0,0 +1,151 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+fn setup_env() -> Result<(TempDir, std::path::PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-c", "Work User", "work@corp.com", "work"])
+        .assert()
+        .success();
+    fs::write(
+        config_path.join("profiles/work.gitconfig"),
+        "[user]\n    email = work@corp.com\n[core]\n    sshCommand = ssh -i /keys/id_work\n",
+    )?;
+
+    let mut config = fs::read_to_string(config_path.join("config.toml"))?;
+    config.push_str("\n[[host_aliases]]\nprofile = \"work\"\nhost = \"github.com\"\n");
+    fs::write(config_path.join("config.toml"), config)?;
+
+    Ok((temp_dir, config_path))
+}
+
+#[test]
+fn test_hosts_apply_is_idempotent() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path) = setup_env()?;
+    let ssh_config = config_path.join("ssh_config");
+    fs::write(&ssh_config, "Host *\n    ServerAliveInterval 30\n")?;
+
+    for _ in 0..2 {
+        Command::new(env!("CARGO_BIN_EXE_naj"))
+            .env("NAJ_CONFIG_PATH", &config_path)
+            .args(["hosts", "apply"])
+            .assert()
+            .success();
+    }
+
+    let content = fs::read_to_string(&ssh_config)?;
+    assert_eq!(content.matches("Host github.com-work").count(), 1);
+    assert!(content.contains("    IdentityFile /keys/id_work\n"));
+    // The managed block comes first, so `Host *` cannot shadow it
+    assert!(content.ends_with("Host *\n    ServerAliveInterval 30\n"));
+
+    let profile = fs::read_to_string(config_path.join("profiles/work.gitconfig"))?;
+    assert_eq!(profile.matches("[url \"git@github.com-work:\"]").count(), 1);
+    assert_eq!(profile.matches("insteadOf = git@github.com:").count(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_hosts_remove_restores_files() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path) = setup_env()?;
+    let ssh_config = config_path.join("ssh_config");
+    let original_ssh = "Host *\n    ServerAliveInterval 30\n";
+    fs::write(&ssh_config, original_ssh)?;
+    let profile_path = config_path.join("profiles/work.gitconfig");
+    let original_profile = fs::read_to_string(&profile_path)?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["hosts", "apply"])
+        .assert()
+        .success();
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["hosts", "remove"])
+        .assert()
+        .success();
+
+    assert_eq!(fs::read_to_string(&ssh_config)?, original_ssh);
+    assert_eq!(fs::read_to_string(&profile_path)?, original_profile);
+
+    Ok(())
+}
+
+#[test]
+fn test_hosts_apply_requires_ssh_key() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path) = setup_env()?;
+    fs::write(
+        config_path.join("profiles/work.gitconfig"),
+        "[user]\n    email = work@corp.com\n",
+    )?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["hosts", "apply"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("naj key gen work"));
+    assert!(!config_path.join("ssh_config").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_switch_drops_alias_rewrites_of_previous_profile() -> Result<(), Box<dyn std::error::Error>>
+{
+    let (temp_dir, config_path) = setup_env()?;
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-c", "Me", "me@home.org", "personal"])
+        .assert()
+        .success();
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["hosts", "apply"])
+        .assert()
+        .success();
+
+    let repo = temp_dir.path().join("repo");
+    fs::create_dir_all(&repo)?;
+    std::process::Command::new("git")
+        .arg("init")
+        .current_dir(&repo)
+        .output()?;
+    std::process::Command::new("git")
+        .args(["remote", "add", "origin", "git@github.com:me/private.git"])
+        .current_dir(&repo)
+        .output()?;
+    let push_url = || -> Result<String, Box<dyn std::error::Error>> {
+        let output = std::process::Command::new("git")
+            .args(["remote", "get-url", "--push", "origin"])
+            .current_dir(&repo)
+            .output()?;
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    };
+
+    // An override switch copies the work profile's rewrite into .git/config
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo)
+        .args(["work", "-s", "override"])
+        .assert()
+        .success();
+    assert_eq!(push_url()?, "git@github.com-work:me/private.git");
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo)
+        .args(["personal", "-f"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Dirty").not());
+    assert_eq!(push_url()?, "git@github.com:me/private.git");
+
+    Ok(())
+}
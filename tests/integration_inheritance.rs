@@ -0,0 +1,142 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+fn setup_env(
+) -> Result<(TempDir, std::path::PathBuf, std::path::PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+    let repo_dir = temp_dir.path().join("repo");
+
+    fs::create_dir_all(&repo_dir)?;
+    std::process::Command::new("git")
+        .arg("init")
+        .current_dir(&repo_dir)
+        .output()?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-c", "Work User", "work@corp.com", "work"])
+        .assert()
+        .success();
+    fs::write(
+        config_path.join("profiles/work.gitconfig"),
+        "[user]\n    name = Work User\n    email = work@corp.com\n[core]\n    sshCommand = ssh -i ~/.ssh/work\n",
+    )?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args([
+            "-c",
+            "Work User",
+            "oss@corp.com",
+            "work-oss",
+            "--extends",
+            "work",
+        ])
+        .assert()
+        .success();
+
+    Ok((temp_dir, config_path, repo_dir))
+}
+
+fn git_get(repo_dir: &std::path::Path, key: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let output = std::process::Command::new("git")
+        .args(["config", "--get", key])
+        .current_dir(repo_dir)
+        .output()?;
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+#[test]
+fn test_child_profile_inherits_parent_keys() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .arg("-l")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("work-oss (extends work)"));
+
+    // Include strategy: git resolves the parent itself
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("work-oss")
+        .assert()
+        .success();
+    assert_eq!(git_get(&repo_dir, "user.email")?, "oss@corp.com");
+    assert_eq!(git_get(&repo_dir, "core.sshCommand")?, "ssh -i ~/.ssh/work");
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("include (work via work-oss)"))
+        .stdout(predicates::str::contains("mismatch").not());
+
+    // Override strategy: naj flattens the chain into the local config
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .args(["work-oss", "-s", "OVERRIDE"])
+        .assert()
+        .success();
+    let local = fs::read_to_string(repo_dir.join(".git/config"))?;
+    assert!(local.contains("oss@corp.com"));
+    assert!(local.contains("ssh -i ~/.ssh/work"));
+    assert!(!local.contains("work@corp.com"));
+
+    // Exec mode
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .args(["work-oss", "config", "core.sshCommand"])
+        .assert()
+        .success()
+        .stdout("ssh -i ~/.ssh/work\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_inheritance_cycle_is_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+    let before = fs::read(repo_dir.join(".git/config"))?;
+
+    let mut work = fs::read_to_string(config_path.join("profiles/work.gitconfig"))?;
+    work.push_str("[include]\n    path = work-oss.gitconfig\n");
+    fs::write(config_path.join("profiles/work.gitconfig"), work)?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("work-oss")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Profile inheritance cycle: work-oss -> work -> work-oss",
+        ));
+    assert_eq!(fs::read(repo_dir.join(".git/config"))?, before);
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["config", "check"])
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains("inheritance cycle"));
+
+    // Unknown parents are refused at creation time
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-c", "X", "x@x.org", "x", "--extends", "nope"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("unknown profile 'nope'"));
+
+    Ok(())
+}
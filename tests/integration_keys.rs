@@ -0,0 +1,199 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+fn setup_env() -> Result<(TempDir, std::path::PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-c", "Work User", "work@corp.com", "work"])
+        .assert()
+        .success();
+
+    Ok((temp_dir, config_path))
+}
+
+#[test]
+fn test_key_gen_sets_ssh_command() -> Result<(), Box<dyn std::error::Error>> {
+    if std::process::Command::new("ssh-keygen")
+        .arg("-?")
+        .output()
+        .is_err()
+    {
+        eprintln!("ssh-keygen not available, skipping");
+        return Ok(());
+    }
+    let (_temp, config_path) = setup_env()?;
+    let key = config_path.join("keys/naj_work_ed25519");
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["key", "gen", "work", "--no-passphrase"])
+        .assert()
+        .success();
+
+    assert!(key.exists());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(key.parent().unwrap())?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+    }
+    let profile = fs::read_to_string(config_path.join("profiles/work.gitconfig"))?;
+    assert!(profile.contains(&format!(
+        "sshCommand = ssh -i {} -o IdentitiesOnly=yes",
+        key.display()
+    )));
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["key", "show", "work"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("ssh-ed25519 "))
+        .stdout(predicate::str::contains("work@corp.com"));
+
+    // An existing key is never overwritten
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["key", "gen", "work", "--no-passphrase"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists"));
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_key_gen_leaves_existing_key_dir_alone() -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if std::process::Command::new("ssh-keygen")
+        .arg("-?")
+        .output()
+        .is_err()
+    {
+        eprintln!("ssh-keygen not available, skipping");
+        return Ok(());
+    }
+    let (temp, config_path) = setup_env()?;
+    let key_dir = temp.path().join("shared");
+    fs::create_dir_all(&key_dir)?;
+    fs::set_permissions(&key_dir, fs::Permissions::from_mode(0o755))?;
+
+    let toml_path = config_path.join("config.toml");
+    let content: String = fs::read_to_string(&toml_path)?
+        .lines()
+        .map(|line| {
+            if line.starts_with("key_dir") {
+                format!("key_dir = \"{}\"\n", key_dir.display())
+            } else {
+                format!("{}\n", line)
+            }
+        })
+        .collect();
+    fs::write(&toml_path, content)?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["key", "gen", "work", "--no-passphrase"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("is accessible by other users"));
+
+    assert!(key_dir.join("naj_work_ed25519").exists());
+    assert_eq!(fs::metadata(&key_dir)?.permissions().mode() & 0o777, 0o755);
+
+    Ok(())
+}
+
+#[test]
+fn test_key_show_follows_ssh_command() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp, config_path) = setup_env()?;
+    let key = temp.path().join("my keys/id_work");
+    fs::create_dir_all(key.parent().unwrap())?;
+    fs::write(
+        temp.path().join("my keys/id_work.pub"),
+        "ssh-ed25519 AAAA work\n",
+    )?;
+    fs::write(
+        config_path.join("profiles/work.gitconfig"),
+        format!(
+            "[user]\n    email = work@corp.com\n[core]\n    sshCommand = ssh -i '{}'\n",
+            key.display()
+        ),
+    )?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["key", "show", "work"])
+        .assert()
+        .success()
+        .stdout("ssh-ed25519 AAAA work\n");
+
+    fs::write(
+        config_path.join("profiles/work.gitconfig"),
+        "[user]\n    email = work@corp.com\n",
+    )?;
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["key", "show", "work"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("naj key gen work"));
+
+    Ok(())
+}
+
+#[test]
+fn test_key_sign_configures_ssh_signing() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp, config_path) = setup_env()?;
+    let key = temp.path().join("id_work");
+    fs::write(
+        temp.path().join("id_work.pub"),
+        "ssh-ed25519 AAAAnew work\n",
+    )?;
+    let signers = config_path.join("profiles/work.allowed_signers");
+    fs::write(
+        &signers,
+        "old@corp.com namespaces=\"git\" ssh-ed25519 AAAAold\nwork@corp.com ssh-ed25519 AAAAnew\n",
+    )?;
+
+    // Signing twice must not duplicate the trusted key
+    for _ in 0..2 {
+        Command::new(env!("CARGO_BIN_EXE_naj"))
+            .env("NAJ_CONFIG_PATH", &config_path)
+            .args(["key", "sign", "work", "--key"])
+            .arg(&key)
+            .assert()
+            .success();
+    }
+
+    let profile = config_path.join("profiles/work.gitconfig");
+    let get = |key: &str| -> Result<String, Box<dyn std::error::Error>> {
+        let output = std::process::Command::new("git")
+            .args(["config", "--file"])
+            .arg(&profile)
+            .args(["--get", key])
+            .output()?;
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    };
+    assert_eq!(get("gpg.format")?, "ssh");
+    assert_eq!(get("commit.gpgsign")?, "true");
+    assert_eq!(get("tag.gpgsign")?, "true");
+    assert!(get("user.signingkey")?.ends_with("id_work.pub"));
+    assert_eq!(
+        get("gpg.ssh.allowedSignersFile")?,
+        signers.to_string_lossy()
+    );
+    assert_eq!(
+        fs::read_to_string(&signers)?,
+        "old@corp.com namespaces=\"git\" ssh-ed25519 AAAAold\nwork@corp.com namespaces=\"git\" ssh-ed25519 AAAAnew\n"
+    );
+
+    Ok(())
+}
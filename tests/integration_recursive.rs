@@ -0,0 +1,67 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn git_init(dir: &Path, args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let output = std::process::Command::new("git")
+        .arg("init")
+        .args(args)
+        .arg(dir)
+        .output()?;
+    assert!(output.status.success());
+    Ok(())
+}
+
+fn git_get(repo_dir: &Path, key: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let output = std::process::Command::new("git")
+        .args(["config", "--get", key])
+        .current_dir(repo_dir)
+        .output()?;
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+#[test]
+fn test_recursive_switch_reports_each_repository() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+    let tree = temp_dir.path().join("tree");
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-c", "Work User", "work@corp.com", "work"])
+        .assert()
+        .success();
+
+    git_init(&tree.join("a"), &[])?;
+    git_init(&tree.join("nested/b"), &[])?;
+    git_init(&tree.join("mirror.git"), &["--bare"])?;
+    git_init(&tree.join("broken"), &[])?;
+    // git refuses to read a malformed config, so this switch must fail
+    fs::write(tree.join("broken/.git/config"), "[core\n")?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["work", "--recursive"])
+        .arg(&tree)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "Switched 2 of 4 repositories to profile 'work'",
+        ))
+        .stdout(predicate::str::contains("skipped").and(predicate::str::contains("mirror.git")))
+        .stdout(predicate::str::contains("failed").and(predicate::str::contains("broken")))
+        .stderr(predicate::str::contains(
+            "Could not switch 1 of 4 repositories",
+        ));
+
+    // The failure did not stop the repositories after it
+    assert_eq!(git_get(&tree.join("a"), "user.email")?, "work@corp.com");
+    assert_eq!(
+        git_get(&tree.join("nested/b"), "user.email")?,
+        "work@corp.com"
+    );
+
+    Ok(())
+}
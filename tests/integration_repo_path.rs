@@ -0,0 +1,148 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn git_get(repo_dir: &Path, key: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let output = std::process::Command::new("git")
+        .args(["config", "--get", key])
+        .current_dir(repo_dir)
+        .output()?;
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+fn setup_env() -> Result<(TempDir, std::path::PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-c", "Work User", "work@corp.com", "work"])
+        .assert()
+        .success();
+
+    Ok((temp_dir, config_path))
+}
+
+#[test]
+fn test_directory_option_switches_other_repository() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp_dir, config_path) = setup_env()?;
+    let repo = temp_dir.path().join("repo");
+    std::process::Command::new("git")
+        .arg("init")
+        .arg(&repo)
+        .output()?;
+
+    // Started outside of any repository
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(temp_dir.path())
+        .arg("-C")
+        .arg(&repo)
+        .arg("work")
+        .assert()
+        .success();
+    assert_eq!(git_get(&repo, "user.email")?, "work@corp.com");
+
+    // `-C` before a subcommand works as with git
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(temp_dir.path())
+        .arg("-C")
+        .arg(&repo)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Profile: work"));
+
+    Ok(())
+}
+
+#[test]
+fn test_separate_git_dir_is_respected() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp_dir, config_path) = setup_env()?;
+    let work_tree = temp_dir.path().join("tree");
+    let git_dir = temp_dir.path().join("store.git");
+    std::fs::create_dir_all(&work_tree)?;
+    std::process::Command::new("git")
+        .arg("init")
+        .arg("--bare")
+        .arg(&git_dir)
+        .output()?;
+
+    // GIT_DIR/GIT_WORK_TREE from the environment
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .env("GIT_DIR", &git_dir)
+        .env("GIT_WORK_TREE", &work_tree)
+        .current_dir(&work_tree)
+        .arg("work")
+        .assert()
+        .success();
+
+    let output = std::process::Command::new("git")
+        .arg("--git-dir")
+        .arg(&git_dir)
+        .args(["config", "--get", "user.email"])
+        .output()?;
+    assert_eq!(String::from_utf8(output.stdout)?.trim(), "work@corp.com");
+
+    // --git-dir on the command line
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(temp_dir.path())
+        .arg("--git-dir")
+        .arg(&git_dir)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Profile: work"));
+
+    Ok(())
+}
+
+#[test]
+fn test_git_dir_does_not_leak_into_recursive_runs() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp_dir, config_path) = setup_env()?;
+    let git_dir = temp_dir.path().join("store.git");
+    let root = temp_dir.path().join("projects");
+    std::process::Command::new("git")
+        .arg("init")
+        .arg("--bare")
+        .arg(&git_dir)
+        .output()?;
+    for name in ["a", "b"] {
+        std::process::Command::new("git")
+            .arg("init")
+            .arg(root.join(name))
+            .output()?;
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(temp_dir.path())
+        .arg("--git-dir")
+        .arg(&git_dir)
+        .args(["work", "--recursive"])
+        .arg(&root)
+        .assert()
+        .success();
+
+    // every repository found gets its own binding; the --git-dir repo is untouched
+    for name in ["a", "b"] {
+        let output = std::process::Command::new("git")
+            .arg("-C")
+            .arg(root.join(name))
+            .args(["config", "--get", "user.email"])
+            .output()?;
+        assert_eq!(String::from_utf8(output.stdout)?.trim(), "work@corp.com");
+    }
+    let output = std::process::Command::new("git")
+        .arg("--git-dir")
+        .arg(&git_dir)
+        .args(["config", "--local", "--get", "user.email"])
+        .output()?;
+    assert!(String::from_utf8(output.stdout)?.trim().is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use assert_cmd::Command;
+use std::fs;
+use tempfile::TempDir;
+
+fn create_profile(config_path: &std::path::Path, id: &str, name: &str, email: &str) {
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", config_path)
+        .args(["-c", name, email, id])
+        .assert()
+        .success();
+}
+
+fn append_config(config_path: &std::path::Path, extra: &str) -> std::io::Result<()> {
+    let toml_path = config_path.join("config.toml");
+    let mut content = fs::read_to_string(&toml_path)?;
+    content.push_str(extra);
+    fs::write(&toml_path, content)
+}
+
+#[test]
+fn test_rules_apply_binds_new_repos() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+    let global_config = temp_dir.path().join("global.gitconfig");
+    let work_dir = temp_dir.path().join("work");
+    let other_dir = temp_dir.path().join("other");
+
+    create_profile(&config_path, "work", "Work User", "work@corp.com");
+    append_config(
+        &config_path,
+        &format!(
+            "\n[[rules]]\npath = \"{}/\"\nprofile = \"work\"\n",
+            work_dir.to_string_lossy()
+        ),
+    )?;
+
+    // Apply twice to verify the global include is only added once
+    for _ in 0..2 {
+        Command::new(env!("CARGO_BIN_EXE_naj"))
+            .env("NAJ_CONFIG_PATH", &config_path)
+            .env("GIT_CONFIG_GLOBAL", &global_config)
+            .args(["rules", "apply"])
+            .assert()
+            .success();
+    }
+
+    let global = fs::read_to_string(&global_config)?;
+    assert_eq!(global.matches("rules.gitconfig").count(), 1);
+
+    let rules = fs::read_to_string(config_path.join("rules.gitconfig"))?;
+    assert!(rules.contains("[includeIf \"gitdir:"));
+    assert!(rules.contains("work.gitconfig"));
+
+    // A fresh repo under the rule path picks up the profile with no naj call
+    for dir in [&work_dir, &other_dir] {
+        let repo = dir.join("repo");
+        fs::create_dir_all(&repo)?;
+        std::process::Command::new("git")
+            .arg("init")
+            .current_dir(&repo)
+            .output()?;
+    }
+
+    let email = |repo: std::path::PathBuf| -> Result<String, std::io::Error> {
+        let output = std::process::Command::new("git")
+            .env("GIT_CONFIG_GLOBAL", &global_config)
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .args(["config", "user.email"])
+            .current_dir(repo)
+            .output()?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+
+    assert_eq!(email(work_dir.join("repo"))?, "work@corp.com");
+    assert_eq!(email(other_dir.join("repo"))?, "");
+
+    Ok(())
+}
+
+#[test]
+fn test_rules_apply_unknown_profile_fails() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+    let global_config = temp_dir.path().join("global.gitconfig");
+
+    create_profile(&config_path, "work", "Work User", "work@corp.com");
+    append_config(
+        &config_path,
+        "\n[[rules]]\npath = \"~/oss/**\"\nprofile = \"missing\"\n",
+    )?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .env("GIT_CONFIG_GLOBAL", &global_config)
+        .args(["rules", "apply"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("missing"));
+
+    assert!(!global_config.exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_auto_selects_profile_from_remote() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+    let repo_dir = temp_dir.path().join("repo");
+
+    create_profile(&config_path, "work", "Work User", "work@corp.com");
+    create_profile(&config_path, "personal", "Me", "me@home.org");
+    append_config(
+        &config_path,
+        "\n[[url_rules]]\npattern = \"github.com:acme/*\"\nprofile = \"work\"\n\
+         \n[[url_rules]]\npattern = \"github.com:me/*\"\nprofile = \"personal\"\n",
+    )?;
+
+    fs::create_dir_all(&repo_dir)?;
+    for args in [
+        vec!["init"],
+        vec!["remote", "add", "fork", "https://github.com/me/app.git"],
+        vec!["remote", "add", "origin", "git@github.com:acme/app.git"],
+    ] {
+        std::process::Command::new("git")
+            .args(&args)
+            .current_dir(&repo_dir)
+            .output()?;
+    }
+
+    // origin takes priority over other matching remotes
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("auto")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("Auto-selected profile 'work'"));
+
+    let git_config = fs::read_to_string(repo_dir.join(".git/config"))?;
+    assert!(git_config.contains("work.gitconfig"));
+    assert!(!git_config.contains("personal.gitconfig"));
+
+    Ok(())
+}
+
+#[test]
+fn test_auto_without_matching_rule_fails() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+
+    create_profile(&config_path, "work", "Work User", "work@corp.com");
+    append_config(
+        &config_path,
+        "\n[[url_rules]]\npattern = \"github.com:acme/*\"\nprofile = \"work\"\n",
+    )?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .env("NAJ_MOCKING", "1")
+        .current_dir(temp_dir.path())
+        .args(["auto", "clone", "https://gitlab.com/acme/app.git"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("No URL rule matches"));
+
+    Ok(())
+}
+
+#[test]
+fn test_later_url_rule_wins() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+
+    create_profile(&config_path, "work", "Work User", "work@corp.com");
+    create_profile(&config_path, "personal", "Me", "me@home.org");
+    append_config(
+        &config_path,
+        "\n[[url_rules]]\npattern = \"github.com:acme/*\"\nprofile = \"work\"\n\
+         \n[[url_rules]]\npattern = \"github.com:acme/oss-*\"\nprofile = \"personal\"\n",
+    )?;
+
+    // Both rules match; the later, more specific one decides
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .env("NAJ_MOCKING", "1")
+        .current_dir(temp_dir.path())
+        .args(["auto", "clone", "git@github.com:acme/oss-tools.git"])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            "Auto-selected profile 'personal'",
+        ));
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .env("NAJ_MOCKING", "1")
+        .current_dir(temp_dir.path())
+        .args(["auto", "clone", "git@github.com:acme/backend.git"])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("Auto-selected profile 'work'"));
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+fn setup_env(
+) -> Result<(TempDir, std::path::PathBuf, std::path::PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+    let repo_dir = temp_dir.path().join("repo");
+
+    fs::create_dir_all(&repo_dir)?;
+    std::process::Command::new("git")
+        .arg("init")
+        .current_dir(&repo_dir)
+        .output()?;
+
+    for (id, name, email) in [
+        ("work", "Work User", "work@corp.com"),
+        ("personal", "Me", "me@home.org"),
+    ] {
+        Command::new(env!("CARGO_BIN_EXE_naj"))
+            .env("NAJ_CONFIG_PATH", &config_path)
+            .args(["-c", name, email, id])
+            .assert()
+            .success();
+    }
+
+    Ok((temp_dir, config_path, repo_dir))
+}
+
+#[test]
+fn test_status_reports_bound_profile() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("work")
+        .assert()
+        .success();
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Profile: work (include)"))
+        .stdout(predicates::str::contains("work@corp.com"))
+        .stdout(predicates::str::contains("include (work)"))
+        .stdout(predicates::str::contains("mismatch").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_status_flags_override_from_other_profile() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("work")
+        .assert()
+        .success();
+
+    // Simulate a leftover override of the personal email
+    std::process::Command::new("git")
+        .args(["config", "user.email", "me@home.org"])
+        .current_dir(&repo_dir)
+        .output()?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("whoami")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("override (personal)"))
+        .stdout(predicates::str::contains(
+            "user.email comes from profile 'personal' but the repo includes 'work'",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_status_reports_profile_from_global_rule() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp_dir, config_path, repo_dir) = setup_env()?;
+    let global_config = temp_dir.path().join("global.gitconfig");
+
+    let toml_path = config_path.join("config.toml");
+    let mut content = fs::read_to_string(&toml_path)?;
+    content.push_str(&format!(
+        "\n[[rules]]\npath = \"{}/\"\nprofile = \"personal\"\n",
+        repo_dir.canonicalize()?.display()
+    ));
+    fs::write(&toml_path, content)?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .env("GIT_CONFIG_GLOBAL", &global_config)
+        .args(["rules", "apply"])
+        .assert()
+        .success();
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .env("GIT_CONFIG_GLOBAL", &global_config)
+        .current_dir(&repo_dir)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Profile: personal (rule)"))
+        .stdout(predicates::str::contains("global include (personal)"));
+
+    Ok(())
+}
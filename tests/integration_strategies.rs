@@ -1,4 +1,5 @@
 use assert_cmd::Command;
+use predicates::prelude::*;
 use std::fs;
 use tempfile::TempDir;
 
@@ -25,7 +26,7 @@ fn setup_env(
 fn create_profile(config_path: &std::path::Path, id: &str, name: &str, email: &str) {
     Command::new(env!("CARGO_BIN_EXE_naj"))
         .env("NAJ_CONFIG_PATH", config_path)
-        .args(&["-c", name, email, id])
+        .args(["-c", name, email, id])
         .assert()
         .success();
 }
@@ -233,3 +234,273 @@ fn test_strategy_override_uppercase_hard() -> Result<(), Box<dyn std::error::Err
 
     Ok(())
 }
+
+fn set_clone_strategy(
+    config_path: &std::path::Path,
+    strategy: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let toml_path = config_path.join("config.toml");
+    let content = fs::read_to_string(&toml_path)?;
+    let new_content = content
+        .lines()
+        .map(|line| {
+            if line.trim().starts_with("clone =") {
+                format!("clone = \"{}\"", strategy)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(&toml_path, new_content)?;
+    Ok(())
+}
+
+#[test]
+fn test_clone_strategy_is_honoured() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp, config_path, source_repo) = setup_env()?;
+    create_profile(&config_path, "cloner", "Cloner", "cl@test.com");
+    set_clone_strategy(&config_path, "OVERRIDE")?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(temp.path())
+        .args(["cloner", "clone", source_repo.to_str().unwrap(), "dest"])
+        .assert()
+        .success();
+
+    let git_config = fs::read_to_string(temp.path().join("dest/.git/config"))?;
+
+    // Override strategy injects values instead of linking the profile
+    assert!(!git_config.contains("cloner.gitconfig"));
+    assert!(git_config.contains("email = cl@test.com"));
+
+    Ok(())
+}
+
+#[test]
+fn test_strategy_flag_overrides_config() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+    create_profile(&config_path, "flagged", "Flagged", "fl@test.com");
+    set_strategy(&config_path, "include")?;
+
+    add_dirty_config(&repo_dir)?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .args(["--strategy", "OVERRIDE", "flagged"])
+        .assert()
+        .success();
+
+    let git_config = fs::read_to_string(repo_dir.join(".git/config"))?;
+    assert!(!git_config.contains("flagged.gitconfig"));
+    assert!(git_config.contains("name = Flagged"));
+    assert!(!git_config.contains("extra = StayHere"));
+
+    // Unknown strategies are rejected on the command line
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .args(["--strategy", "overide", "flagged"])
+        .assert()
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn test_unknown_strategy_is_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+    create_profile(&config_path, "typo", "Typo", "typo@test.com");
+    set_strategy(&config_path, "overide")?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("typo")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("unknown strategy 'overide'"))
+        .stderr(predicates::str::contains("line"));
+
+    // Spelled-out aliases are accepted
+    set_strategy(&config_path, "include-hard")?;
+    add_dirty_config(&repo_dir)?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("typo")
+        .assert()
+        .success();
+
+    let git_config = fs::read_to_string(repo_dir.join(".git/config"))?;
+    assert!(!git_config.contains("name = DirtyUser"));
+
+    Ok(())
+}
+
+#[test]
+fn test_override_preserves_multivars_and_quoting() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+    create_profile(&config_path, "multi", "Multi", "multi@test.com");
+    set_strategy(&config_path, "override")?;
+
+    let profile_path = config_path.join("profiles").join("multi.gitconfig");
+    let mut profile = fs::read_to_string(&profile_path)?;
+    profile.push_str(
+        "[url \"git@github.com-Work:\"]\n\
+         \tinsteadOf = https://github.com/acme/\n\
+         \tinsteadOf = git@github.com:acme/\n\
+         [core]\n\
+         \tsshCommand = \"ssh -i ~/.ssh/id #work\" ; comment\n",
+    );
+    fs::write(&profile_path, profile)?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("multi")
+        .assert()
+        .success();
+
+    let get_all = |key: &str| -> Result<String, std::io::Error> {
+        let output = std::process::Command::new("git")
+            .args(["config", "--local", "--get-all", key])
+            .current_dir(&repo_dir)
+            .output()?;
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    };
+
+    // Both values survive, under the case-preserved subsection
+    assert_eq!(
+        get_all("url.git@github.com-Work:.insteadOf")?,
+        "https://github.com/acme/\ngit@github.com:acme/\n"
+    );
+    assert_eq!(get_all("core.sshCommand")?, "ssh -i ~/.ssh/id #work\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_dirty_config_warning_is_exact() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+    create_profile(&config_path, "exact", "Exact", "exact@test.com");
+    set_strategy(&config_path, "include")?;
+
+    // Mentions of sensitive names outside of real keys are not dirt
+    std::process::Command::new("git")
+        .args([
+            "config",
+            "remote.origin.url",
+            "https://example.com/[user]/sshCommand.git",
+        ])
+        .current_dir(&repo_dir)
+        .output()?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("exact")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Dirty").not());
+
+    add_dirty_config(&repo_dir)?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("exact")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Dirty Local Config Detected"))
+        .stdout(predicates::str::contains("user.extra"));
+
+    Ok(())
+}
+
+#[test]
+fn test_force_clears_dirty_config_warning() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+    create_profile(&config_path, "tidy", "Tidy", "tidy@test.com");
+    set_strategy(&config_path, "include")?;
+
+    // Blacklisted sections with a subsection are dirt as well
+    add_dirty_config(&repo_dir)?;
+    std::process::Command::new("git")
+        .args(["config", "gpg.ssh.allowedSignersFile", "/tmp/other_signers"])
+        .current_dir(&repo_dir)
+        .output()?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("tidy")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("gpg.ssh.allowedsignersfile"));
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .args(["tidy", "-f", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("gpg.ssh"));
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .args(["tidy", "-f"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Dirty").not());
+
+    // Nothing is left for the next switch to warn about
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .arg("tidy")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Dirty").not());
+
+    Ok(())
+}
+
+#[test]
+fn test_failed_switch_restores_local_config() -> Result<(), Box<dyn std::error::Error>> {
+    let (_temp, config_path, repo_dir) = setup_env()?;
+    create_profile(&config_path, "atomic", "Atomic", "atomic@test.com");
+    add_dirty_config(&repo_dir)?;
+    let before = fs::read(repo_dir.join(".git/config"))?;
+
+    // A command-line identity defeats the hard switch, so verification fails
+    // after the local config has already been sanitised.
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "user.email")
+        .env("GIT_CONFIG_VALUE_0", "someone@else.org")
+        .current_dir(&repo_dir)
+        .args(["-f", "atomic"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("left unchanged"))
+        .stderr(predicates::str::contains("someone@else.org"));
+
+    assert_eq!(fs::read(repo_dir.join(".git/config"))?, before);
+
+    // Without the interference the same switch goes through
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&repo_dir)
+        .args(["-f", "atomic"])
+        .assert()
+        .success();
+    assert_ne!(fs::read(repo_dir.join(".git/config"))?, before);
+
+    Ok(())
+}
@@ -28,7 +28,7 @@ fn test_profile_creation_and_listing() -> Result<(), Box<dyn std::error::Error>>
 
     let mut cmd = Command::new(env!("CARGO_BIN_EXE_naj"));
     cmd.env("NAJ_CONFIG_PATH", config_path)
-        .args(&["-c", "Test User", "test@example.com", "test_user"])
+        .args(["-c", "Test User", "test@example.com", "test_user"])
         .assert()
         .success();
 
@@ -59,14 +59,14 @@ fn test_duplicate_creation_failure() -> Result<(), Box<dyn std::error::Error>> {
     // Create first
     Command::new(env!("CARGO_BIN_EXE_naj"))
         .env("NAJ_CONFIG_PATH", config_path)
-        .args(&["-c", "User", "u@e.com", "dup_test"])
+        .args(["-c", "User", "u@e.com", "dup_test"])
         .assert()
         .success();
 
     // Create duplicate
     Command::new(env!("CARGO_BIN_EXE_naj"))
         .env("NAJ_CONFIG_PATH", config_path)
-        .args(&["-c", "User2", "u2@e.com", "dup_test"])
+        .args(["-c", "User2", "u2@e.com", "dup_test"])
         .assert()
         .failure(); // Should fail
 
@@ -82,7 +82,7 @@ fn test_remove_profile() -> Result<(), Box<dyn std::error::Error>> {
     // Create
     Command::new(env!("CARGO_BIN_EXE_naj"))
         .env("NAJ_CONFIG_PATH", config_path)
-        .args(&["-c", "User", "u@e.com", "rem_test"])
+        .args(["-c", "User", "u@e.com", "rem_test"])
         .assert()
         .success();
     assert!(profile_path.exists());
@@ -90,7 +90,7 @@ fn test_remove_profile() -> Result<(), Box<dyn std::error::Error>> {
     // Remove
     Command::new(env!("CARGO_BIN_EXE_naj"))
         .env("NAJ_CONFIG_PATH", config_path)
-        .args(&["-r", "rem_test"])
+        .args(["-r", "rem_test"])
         .assert()
         .success();
     assert!(!profile_path.exists());
@@ -98,7 +98,7 @@ fn test_remove_profile() -> Result<(), Box<dyn std::error::Error>> {
     // Remove non-existent
     Command::new(env!("CARGO_BIN_EXE_naj"))
         .env("NAJ_CONFIG_PATH", config_path)
-        .args(&["-r", "rem_test"])
+        .args(["-r", "rem_test"])
         .assert()
         .failure();
 
@@ -113,7 +113,7 @@ fn test_exec_dry_run_injection_strict() -> Result<(), Box<dyn std::error::Error>
     // Create a profile first
     Command::new(env!("CARGO_BIN_EXE_naj"))
         .env("NAJ_CONFIG_PATH", config_path)
-        .args(&["-c", "Test", "test@e.com", "p1"])
+        .args(["-c", "Test", "test@e.com", "p1"])
         .assert()
         .success();
 
@@ -121,7 +121,7 @@ fn test_exec_dry_run_injection_strict() -> Result<(), Box<dyn std::error::Error>
     let mut cmd = Command::new(env!("CARGO_BIN_EXE_naj"));
     cmd.env("NAJ_CONFIG_PATH", config_path)
         .env("NAJ_MOCKING", "1")
-        .args(&["p1", "commit", "-m", "foo"])
+        .args(["p1", "commit", "-m", "foo"])
         .assert()
         .success()
         .stderr(predicates::str::contains("user.name="))
@@ -153,7 +153,7 @@ fn test_switch_mode_persistent() -> Result<(), Box<dyn std::error::Error>> {
     // Create profile
     Command::new(env!("CARGO_BIN_EXE_naj"))
         .env("NAJ_CONFIG_PATH", &config_path)
-        .args(&["-c", "Switch User", "s@e.com", "switch_test"])
+        .args(["-c", "Switch User", "s@e.com", "switch_test"])
         .assert()
         .success();
 
@@ -202,7 +202,7 @@ fn test_switch_force_mode_sanitization() -> Result<(), Box<dyn std::error::Error
     // Create profile
     Command::new(env!("CARGO_BIN_EXE_naj"))
         .env("NAJ_CONFIG_PATH", &config_path)
-        .args(&["-c", "Force User", "f@e.com", "force_test"])
+        .args(["-c", "Force User", "f@e.com", "force_test"])
         .assert()
         .success();
 
@@ -210,7 +210,7 @@ fn test_switch_force_mode_sanitization() -> Result<(), Box<dyn std::error::Error
     Command::new(env!("CARGO_BIN_EXE_naj"))
         .env("NAJ_CONFIG_PATH", &config_path)
         .current_dir(&repo_dir)
-        .args(&["force_test", "-f"])
+        .args(["force_test", "-f"])
         .assert()
         .success();
 
@@ -245,7 +245,7 @@ fn test_setup_mode_local_clone() -> Result<(), Box<dyn std::error::Error>> {
     // 2. Create Profile
     Command::new(env!("CARGO_BIN_EXE_naj"))
         .env("NAJ_CONFIG_PATH", &config_path)
-        .args(&["-c", "CloneUser", "c@e.com", "clone_test"])
+        .args(["-c", "CloneUser", "c@e.com", "clone_test"])
         .assert()
         .success();
 
@@ -260,7 +260,7 @@ fn test_setup_mode_local_clone() -> Result<(), Box<dyn std::error::Error>> {
     Command::new(env!("CARGO_BIN_EXE_naj"))
         .env("NAJ_CONFIG_PATH", &config_path)
         .current_dir(temp_dir.path()) // Execute in temp root
-        .args(&[
+        .args([
             "clone_test",
             "clone",
             source_repo.to_str().unwrap(),
@@ -285,3 +285,221 @@ fn test_setup_mode_local_clone() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_config_check() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path();
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", config_path)
+        .args(["-c", "User", "u@e.com", "checked"])
+        .assert()
+        .success();
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", config_path)
+        .args(["config", "check"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("profile 'checked'"));
+
+    // A rule pointing at a missing profile is reported
+    let toml_path = config_path.join("config.toml");
+    let mut content = fs::read_to_string(&toml_path)?;
+    content.push_str("\n[[rules]]\npath = \"~/work/**\"\nprofile = \"missing\"\n");
+    fs::write(&toml_path, content)?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", config_path)
+        .args(["config", "check"])
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains("unknown profile 'missing'"));
+
+    // So is a host alias for a missing profile
+    let mut content = fs::read_to_string(&toml_path)?;
+    content.push_str("\n[[host_aliases]]\nprofile = \"gone\"\nhost = \"github.com\"\n");
+    fs::write(&toml_path, content)?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", config_path)
+        .args(["config", "check"])
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains(
+            "host alias 'github.com-gone' refers to unknown profile 'gone'",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_config_check_does_not_create_config() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["config", "check"])
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains("config.toml\" does not exist"));
+    assert!(!config_path.exists());
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_edit_profile_validates_result() -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-c", "Test User", "test@example.com", "test_user"])
+        .assert()
+        .success();
+
+    // Fake editors that append to the file they are given
+    let write_editor = |name: &str, line: &str| -> std::io::Result<std::path::PathBuf> {
+        let path = temp_dir.path().join(name);
+        fs::write(&path, format!("#!/bin/sh\necho '{}' >> \"$1\"\n", line))?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        Ok(path)
+    };
+    let good = write_editor("good.sh", "[core]\n    sshCommand = ssh -i key")?;
+    let bad = write_editor("bad.sh", "[broken")?;
+
+    // $VISUAL wins over $EDITOR
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .env("VISUAL", &good)
+        .env("EDITOR", &bad)
+        .args(["--edit", "test_user"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Profile 'test_user' is valid"));
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["show", "test_user"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("user.email"))
+        .stdout(predicates::str::contains("test@example.com"))
+        .stdout(predicates::str::contains("ssh -i key"));
+
+    // Without a terminal a broken result is reported instead of re-opening
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .env_remove("VISUAL")
+        .env("EDITOR", &bad)
+        .args(["-e", "test_user"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Profile 'test_user' is invalid"))
+        .stderr(predicates::str::contains("Malformed section header"));
+
+    Ok(())
+}
+
+#[test]
+fn test_profile_ids_cannot_escape_profile_dir() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+    let victim = temp_dir.path().join("victim.gitconfig");
+    fs::write(&victim, "[user]\n    name = Victim\n")?;
+
+    for id in [
+        "../victim",
+        "../../victim",
+        "/tmp/victim",
+        "a/b",
+        ".hidden",
+        "",
+    ] {
+        Command::new(env!("CARGO_BIN_EXE_naj"))
+            .env("NAJ_CONFIG_PATH", &config_path)
+            .args(["-r", id])
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("profile ID"));
+
+        Command::new(env!("CARGO_BIN_EXE_naj"))
+            .env("NAJ_CONFIG_PATH", &config_path)
+            .args(["-c", "Name", "a@b.c", id])
+            .assert()
+            .failure();
+    }
+    assert!(victim.exists());
+
+    // Names naj dispatches on cannot become profiles
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-c", "Name", "a@b.c", "clone"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("reserved"));
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-c", "Name", "a@b.c", "team-a_1.2"])
+        .assert()
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn test_git_command_without_profile_is_explained() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+
+    for id in ["work", "personal"] {
+        Command::new(env!("CARGO_BIN_EXE_naj"))
+            .env("NAJ_CONFIG_PATH", &config_path)
+            .args(["-c", "Name", "a@b.c", id])
+            .assert()
+            .success();
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["commit", "-m", "fix it"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Did you mean `naj <profile> commit -m 'fix it'`?",
+        ))
+        .stderr(predicates::str::contains(
+            "Available profiles: personal, work",
+        ));
+
+    // Near-miss IDs get a suggestion
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .arg("wrok")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Did you mean 'work'?"));
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-r", "persona"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Did you mean 'personal'?"));
+
+    // Git builtins cannot become profiles
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .args(["-c", "Name", "a@b.c", "log"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("'log' is a git command"));
+
+    Ok(())
+}
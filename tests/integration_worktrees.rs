@@ -0,0 +1,238 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    let output = std::process::Command::new("git")
+        .args([
+            "-c",
+            "user.name=Setup",
+            "-c",
+            "user.email=setup@example.com",
+        ])
+        .args(["-c", "protocol.file.allow=always"])
+        .args(args)
+        .current_dir(dir)
+        .output()?;
+    assert!(output.status.success(), "git {:?} failed", args);
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+fn git_get(repo_dir: &Path, key: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let output = std::process::Command::new("git")
+        .args(["config", "--get", key])
+        .current_dir(repo_dir)
+        .output()?;
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+fn setup_env() -> Result<(TempDir, PathBuf), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config_path = temp_dir.path().join("config");
+
+    for (name, email, id) in [
+        ("Work User", "work@corp.com", "work"),
+        ("Personal User", "me@home.org", "personal"),
+    ] {
+        Command::new(env!("CARGO_BIN_EXE_naj"))
+            .env("NAJ_CONFIG_PATH", &config_path)
+            .args(["-c", name, email, id])
+            .assert()
+            .success();
+    }
+
+    Ok((temp_dir, config_path))
+}
+
+// A repository with one commit and a linked worktree next to it
+fn setup_worktrees(root: &Path) -> Result<(PathBuf, PathBuf), Box<dyn std::error::Error>> {
+    let main = root.join("main");
+    std::fs::create_dir_all(&main)?;
+    git(&main, &["init"])?;
+    git(&main, &["commit", "--allow-empty", "-m", "init"])?;
+    git(&main, &["worktree", "add", "../linked"])?;
+    Ok((main, root.join("linked")))
+}
+
+#[test]
+fn test_linked_worktree_shares_binding() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp_dir, config_path) = setup_env()?;
+    let (main, linked) = setup_worktrees(temp_dir.path())?;
+
+    // `.git` is a file in the linked worktree; the shared config is still found
+    git(&main, &["config", "user.name", "Manual Name"])?;
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&linked)
+        .arg("work")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Dirty Local Config Detected"));
+
+    assert_eq!(git_get(&main, "user.email")?, "work@corp.com");
+    assert_eq!(git_get(&linked, "user.email")?, "work@corp.com");
+    Ok(())
+}
+
+#[test]
+fn test_worktree_binds_single_worktree() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp_dir, config_path) = setup_env()?;
+    let (main, linked) = setup_worktrees(temp_dir.path())?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&main)
+        .arg("work")
+        .assert()
+        .success();
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&linked)
+        .args(["personal", "--worktree"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Enabled extensions.worktreeConfig",
+        ));
+
+    assert_eq!(git_get(&linked, "user.email")?, "me@home.org");
+    assert_eq!(git_get(&main, "user.email")?, "work@corp.com");
+
+    // Once enabled, plain switches stay per worktree
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&main)
+        .args(["personal", "-f"])
+        .assert()
+        .success();
+    assert_eq!(git_get(&main, "user.email")?, "me@home.org");
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&linked)
+        .arg("work")
+        .assert()
+        .success();
+    assert_eq!(git_get(&linked, "user.email")?, "work@corp.com");
+    assert_eq!(git_get(&main, "user.email")?, "me@home.org");
+
+    Ok(())
+}
+
+#[test]
+fn test_worktree_switch_drops_shared_binding() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp_dir, config_path) = setup_env()?;
+    let (main, linked) = setup_worktrees(temp_dir.path())?;
+
+    let profile = config_path.join("profiles/work.gitconfig");
+    let mut content = std::fs::read_to_string(&profile)?;
+    content.push_str("[core]\n    sshCommand = ssh -i /keys/id_work\n");
+    std::fs::write(&profile, content)?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&main)
+        .arg("work")
+        .assert()
+        .success();
+    git(&main, &["config", "commit.gpgsign", "true"])?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&linked)
+        .args(["personal", "--worktree"])
+        .assert()
+        .success();
+
+    // Nothing of the work binding is left underneath the personal one
+    assert_eq!(git_get(&linked, "user.email")?, "me@home.org");
+    assert_eq!(git_get(&linked, "core.sshCommand")?, "");
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&linked)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Profile: personal"))
+        .stdout(predicate::str::contains("Multiple profiles").not());
+
+    // The main worktree keeps everything it had, now in its own config
+    assert_eq!(git_get(&main, "user.email")?, "work@corp.com");
+    assert_eq!(git_get(&main, "core.sshCommand")?, "ssh -i /keys/id_work");
+    assert_eq!(git_get(&main, "commit.gpgsign")?, "true");
+    let shared = std::fs::read_to_string(main.join(".git/config"))?;
+    assert!(!shared.contains("work.gitconfig"));
+    assert!(!shared.contains("gpgsign"));
+
+    // A hard switch in the main worktree now clears all of it
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&main)
+        .args(["personal", "-f"])
+        .assert()
+        .success();
+    assert_eq!(git_get(&main, "core.sshCommand")?, "");
+    assert_eq!(git_get(&main, "commit.gpgsign")?, "");
+
+    Ok(())
+}
+
+#[test]
+fn test_status_reports_worktree_override() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp_dir, config_path) = setup_env()?;
+    let (_main, linked) = setup_worktrees(temp_dir.path())?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&linked)
+        .args(["personal", "--worktree", "--strategy", "override"])
+        .assert()
+        .success();
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&linked)
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("worktree override (personal)"))
+        .stdout(predicate::str::contains("Profile: personal (override)"));
+
+    Ok(())
+}
+
+#[test]
+fn test_submodules_follow_superproject() -> Result<(), Box<dyn std::error::Error>> {
+    let (temp_dir, config_path) = setup_env()?;
+    let root = temp_dir.path();
+
+    let lib = root.join("lib");
+    std::fs::create_dir_all(&lib)?;
+    git(&lib, &["init"])?;
+    git(&lib, &["commit", "--allow-empty", "-m", "init"])?;
+
+    let app = root.join("app");
+    std::fs::create_dir_all(&app)?;
+    git(&app, &["init"])?;
+    git(&app, &["submodule", "add", "../lib", "vendor/lib"])?;
+
+    Command::new(env!("CARGO_BIN_EXE_naj"))
+        .env("NAJ_CONFIG_PATH", &config_path)
+        .current_dir(&app)
+        .args(["work", "--submodules"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Switched 1 of 1 submodules to profile 'work'",
+        ));
+
+    assert_eq!(git_get(&app, "user.email")?, "work@corp.com");
+    assert_eq!(
+        git_get(&app.join("vendor/lib"), "user.email")?,
+        "work@corp.com"
+    );
+
+    Ok(())
+}